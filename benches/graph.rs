@@ -0,0 +1,69 @@
+//! `cargo bench --bench graph`, times the pathfinding on the vanilla map with every
+//! transition visited (the biggest graph a seed can have).
+//!
+//! Plain `std::time` timing instead of a benchmark framework, run it in release to compare
+//! changes.
+
+use std::{hint::black_box, time::Instant};
+
+use rusty_map_view::{
+    data::{self, RandoData},
+    graph::{Destination, Graph, PathCosts},
+};
+
+fn bench(name: &str, iterations: u32, mut f: impl FnMut()) {
+    // warm up
+    for _ in 0..iterations.div_ceil(10) {
+        f();
+    }
+
+    let start = Instant::now();
+    for _ in 0..iterations {
+        f();
+    }
+    let per_iteration = start.elapsed() / iterations;
+    println!("{name:<32} {per_iteration:>12.2?}/iter ({iterations} iterations)");
+}
+
+fn main() {
+    let map_data = data::load_mapdata(data::EMBEDDED_MAPDATA).expect("embedded map data");
+    let rando_data = RandoData::vanilla(&map_data);
+    let graph = Graph::new(&map_data, &rando_data, PathCosts::default());
+
+    let mut rooms: Vec<&String> = map_data.rooms.keys().collect();
+    rooms.sort();
+    // a spread of rooms all over the map
+    let targets: Vec<&String> = rooms.iter().step_by(rooms.len() / 16).copied().collect();
+
+    println!("{} transitions", graph.len());
+
+    bench("Graph::new", 100, || {
+        black_box(Graph::new(&map_data, &rando_data, PathCosts::default()));
+    });
+
+    bench("find_path (16 targets)", 100, || {
+        for dst in &targets {
+            black_box(graph.find_path("Town", dst));
+        }
+    });
+
+    bench("find_path (every pair of 16)", 10, || {
+        for src in &targets {
+            for dst in &targets {
+                black_box(graph.find_path(src, dst));
+            }
+        }
+    });
+
+    bench("nearest stag", 100, || {
+        black_box(graph.nearest(&map_data, "Tutorial_01", Destination::Stag));
+    });
+
+    bench("rooms_within (depth 2)", 1000, || {
+        black_box(graph.rooms_within("Town", 2));
+    });
+
+    bench("reachable_rooms", 100, || {
+        black_box(graph.reachable_rooms("Town"));
+    });
+}
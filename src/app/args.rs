@@ -0,0 +1,95 @@
+use std::path::PathBuf;
+
+use rusty_map_view::{connection, util::flags::Flags};
+
+pub const USAGE: &str = "\
+Usage: rusty-map-view [OPTIONS]
+       rusty-map-view validate [--mapdata <FILE>]
+
+Commands:
+    validate                 Check the map data for consistency and exit (nonzero if there are errors)
+
+Options:
+    --url <URL>              WebSocket endpoint of the TangledMapView mod [default: ws://localhost:7900/ws]
+    --mapdata <FILE>         Load map data from FILE instead of the built in copy, reloading it when it changes
+    --save <FILE>            Open a save file (modded .json save, or an exported loadSave/PolymorphicModData)
+    --record <FILE>          Write every received message to FILE (JSON lines)
+    --replay <FILE>          Play back a recording instead of connecting to the game
+    --replay-speed <SPEED>   Playback speed multiplier from 0.01 to 100, 0 sends everything at once [default: 1]
+    -h, --help               Print this message
+
+The layout of a save can be printed without starting the viewer, see rusty-map-layout --help.";
+
+/// Playback speeds `--replay-speed` accepts besides 0.
+const REPLAY_SPEEDS: std::ops::RangeInclusive<f32> = 0.01..=100.0;
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Command {
+    Run,
+    Validate,
+}
+
+pub struct Args {
+    pub command: Command,
+    pub url: String,
+    pub mapdata: Option<PathBuf>,
+    pub save: Option<PathBuf>,
+    pub record: Option<PathBuf>,
+    pub replay: Option<PathBuf>,
+    pub replay_speed: f32,
+}
+
+impl Default for Args {
+    fn default() -> Self {
+        Self {
+            command: Command::Run,
+            url: connection::DEFAULT_URL.into(),
+            mapdata: None,
+            save: None,
+            record: None,
+            replay: None,
+            replay_speed: 1.0,
+        }
+    }
+}
+
+pub enum ParseResult {
+    Run(Args),
+    Help,
+}
+
+impl Args {
+    pub fn parse(args: impl IntoIterator<Item = String>) -> Result<ParseResult, String> {
+        let mut parsed = Args::default();
+
+        let mut args = args.into_iter().peekable();
+        if args.peek().map(String::as_str) == Some("validate") {
+            parsed.command = Command::Validate;
+            args.next();
+        }
+
+        let mut flags = Flags::new(args);
+        while let Some(flag) = flags.next_flag() {
+            match flag.as_str() {
+                "-h" | "--help" => return Ok(ParseResult::Help),
+                "--url" => parsed.url = flags.value("--url")?,
+                "--mapdata" => parsed.mapdata = Some(flags.value("--mapdata")?.into()),
+                "--save" => parsed.save = Some(flags.value("--save")?.into()),
+                "--record" => parsed.record = Some(flags.value("--record")?.into()),
+                "--replay" => parsed.replay = Some(flags.value("--replay")?.into()),
+                "--replay-speed" => {
+                    parsed.replay_speed = Some(flags.parse("--replay-speed")?)
+                        .filter(|s: &f32| *s == 0.0 || REPLAY_SPEEDS.contains(s))
+                        .ok_or("Invalid value for --replay-speed")?;
+                },
+                _ => return Err(format!("Unknown argument '{flag}'")),
+            }
+        }
+
+        if parsed.record.is_some() && parsed.replay.is_some() {
+            return Err("--record and --replay can't be used together".into());
+        }
+
+        Ok(ParseResult::Run(parsed))
+    }
+}
@@ -0,0 +1,50 @@
+use std::time::{Duration, Instant};
+
+use rusty_map_view::inbox::Received;
+
+/// Per-frame message processing stats, shown in the debug UI.
+#[derive(Default)]
+pub struct InboxStats {
+    pub last_batch: usize,
+    pub max_batch: usize,
+    pub total: usize,
+    pub coalesced: usize,
+    pub last_latency: Duration,
+    pub max_latency: Duration,
+}
+
+impl InboxStats {
+    pub fn record_batch(&mut self, batch: &[Received], now: Instant) {
+        if batch.is_empty() {
+            return;
+        }
+
+        self.last_batch = batch.len();
+        self.max_batch = self.max_batch.max(batch.len());
+        self.total += batch.len();
+
+        // the oldest message in the batch waited the longest
+        self.last_latency = now.saturating_duration_since(batch[0].time);
+        self.max_latency = self.max_latency.max(self.last_latency);
+    }
+
+    pub fn fill_debug_egui(&mut self, ui: &mut egui::Ui, depth: usize) {
+        ui.label(format!("queued: {depth}"));
+        ui.label(format!(
+            "last batch: {} (max {})",
+            self.last_batch, self.max_batch
+        ));
+        ui.label(format!(
+            "latency: {:.1}ms (max {:.1}ms)",
+            self.last_latency.as_secs_f32() * 1000.0,
+            self.max_latency.as_secs_f32() * 1000.0
+        ));
+        ui.label(format!(
+            "total: {} ({} playerPos coalesced)",
+            self.total, self.coalesced
+        ));
+        if ui.button("reset").clicked() {
+            *self = Self::default();
+        }
+    }
+}
@@ -0,0 +1,5 @@
+pub mod args;
+pub mod inbox_stats;
+pub mod render;
+pub mod settings;
+pub mod util;
@@ -0,0 +1,116 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use ggez::{
+    graphics::{self, Color, DrawParam, Drawable, Font, PxScale},
+    GameResult,
+};
+use rusty_map_view::{
+    data::MapData,
+    layout::Positions,
+    util::{hull::convex_hull, rect::Point},
+};
+
+use crate::app::util::{color_ext::ColorExt, transform_stack::TransformStack};
+
+/// Space between the rooms of an area and its hull.
+const HULL_PADDING: f32 = 40.0;
+
+/// Stroke and fill color of the rooms of an area, the same as the in game map.
+#[allow(clippy::match_same_arms)]
+#[must_use]
+pub fn area_colors(area: Option<&str>) -> (Color, Color) {
+    let (stroke_color, fill_color) = match area {
+        Some("Abyss") => (0xADACAD, 0x2D2D2D),          //Ancient Basin
+        Some("Cliffs") => (0x6B6B6B, 0x1B1B1B),         //Howling Cliffs
+        Some("Crossroads") => (0x9DC1DA, 0x2B353B),     //Forgotten Crossroads
+        Some("Deepnest") => (0x9AABC2, 0x262B30),       //Deepnest
+        Some("Deepnest_East") => (0xDFD1BE, 0x34312C),  //Kingdom's Edge
+        Some("FogCanyon") => (0xF3C8EB, 0x3C323A),      //Fog Canyon
+        Some("Fungus1") => (0xDCFFD0, 0x313D2E),        //Greenpath
+        Some("Fungus2") => (0xFAFFD3, 0x3B3D32),        //Fungal Wastes
+        Some("Fungus3") => (0x96B999, 0x232B23),        //Queen's Gardens
+        Some("Hive") => (0xFFF7A3, 0x3B3824),           //The Hive
+        Some("Mines") => (0xE4BEE8, 0x372F38),          //Crystal Peak
+        Some("RestingGrounds") => (0xFEC7A2, 0x382D24), //Resting Grounds
+        Some("Room") => (0xFEA2AD, 0x382425),           //Room
+        Some("Ruins1" | "Ruins2") => (0xB8C3FF, 0x292C3C), //City of Tears
+        Some("Town") => (0xA2A2A2, 0x2B2B2B),           //Dirtmouth
+        Some("Waterways") => (0x98FFFF, 0x243D3C),      //Royal Waterways
+        Some("White_Palace") => (0xD8D8D8, 0x333333),   //White Palace
+        _ => (0xA2A2A2, 0x2B2B2B),
+    };
+
+    (
+        Color::from_rgb_u32(stroke_color),
+        Color::from_rgb_u32(fill_color),
+    )
+}
+
+/// Draws a translucent outline around the `rooms` of each area with the area's name above it.
+pub fn draw_area_hulls(
+    ctx: &mut ggez::Context,
+    transform: &TransformStack,
+    map_data: &MapData,
+    rooms: &BTreeSet<String>,
+    positions: &Positions,
+) -> GameResult {
+    let mut corners: BTreeMap<&str, Vec<Point>> = BTreeMap::new();
+    for key in rooms {
+        let (Some(room), Some(&(x, y))) = (map_data.rooms.get(key), positions.get(key)) else {
+            continue;
+        };
+        let Some(area) = room.area.as_deref() else {
+            continue;
+        };
+
+        let mut bounds = room.bounds_at((x, y));
+        bounds.inflate(HULL_PADDING);
+        corners.entry(area).or_default().extend([
+            Point { x: bounds.left(), y: bounds.top() },
+            Point { x: bounds.right(), y: bounds.top() },
+            Point { x: bounds.right(), y: bounds.bottom() },
+            Point { x: bounds.left(), y: bounds.bottom() },
+        ]);
+    }
+
+    let transparent = Color::from_rgba(0, 0, 0, 0);
+    for (area, corners) in corners {
+        let hull = convex_hull(corners);
+        let (stroke_color, fill_color) = area_colors(Some(area));
+        let points: Vec<[f32; 2]> = hull.iter().map(|p| [p.x, p.y]).collect();
+
+        let fill = graphics::Mesh::new_polygon(
+            ctx,
+            graphics::DrawMode::fill(),
+            &points,
+            fill_color.lerp(&transparent, 0.6),
+        )?;
+        graphics::draw(ctx, &fill, transform)?;
+
+        let stroke = graphics::Mesh::new_polygon(
+            ctx,
+            graphics::DrawMode::stroke(3.0),
+            &points,
+            stroke_color.lerp(&transparent, 0.7),
+        )?;
+        graphics::draw(ctx, &stroke, transform)?;
+
+        // name centered above the hull
+        let left = hull.iter().map(|p| p.x).fold(f32::MAX, f32::min);
+        let right = hull.iter().map(|p| p.x).fold(f32::MIN, f32::max);
+        let top = hull.iter().map(|p| p.y).fold(f32::MAX, f32::min);
+        let name = map_data.areas.get(area).map_or(area, String::as_str);
+        let mut text = graphics::Text::new(name);
+        text.set_font(Font::default(), PxScale::from(32.0));
+        let width = text.width(ctx);
+
+        let mut transform = transform.clone();
+        transform.translate((left + right - width) / 2.0, top - 36.0);
+        text.draw(
+            ctx,
+            DrawParam::from(&transform).color(stroke_color.lerp(&transparent, 0.4)),
+        )?;
+    }
+
+    Ok(())
+}
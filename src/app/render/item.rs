@@ -0,0 +1,88 @@
+use ggez::{
+    graphics::{self, Color},
+    GameResult,
+};
+
+/// Shape an item location is drawn as, so pools can be told apart at a glance.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum ItemShape {
+    Circle,
+    Diamond,
+    Square,
+    Triangle,
+    Star,
+}
+
+impl ItemShape {
+    /// White mesh of the shape about 8 units across, centered on the origin.
+    pub fn mesh(self, ctx: &mut ggez::Context) -> GameResult<graphics::Mesh> {
+        let mode = graphics::DrawMode::fill();
+        match self {
+            ItemShape::Circle => {
+                graphics::Mesh::new_circle(ctx, mode, [0.0, 0.0], 4.0, 2.0, Color::WHITE)
+            },
+            ItemShape::Diamond => graphics::Mesh::new_polygon(
+                ctx,
+                mode,
+                &[[0.0, -5.0], [4.0, 0.0], [0.0, 5.0], [-4.0, 0.0]],
+                Color::WHITE,
+            ),
+            ItemShape::Square => graphics::Mesh::new_rectangle(
+                ctx,
+                mode,
+                graphics::Rect::new(-3.5, -3.5, 7.0, 7.0),
+                Color::WHITE,
+            ),
+            ItemShape::Triangle => graphics::Mesh::new_polygon(
+                ctx,
+                mode,
+                &[[0.0, -4.5], [4.5, 3.5], [-4.5, 3.5]],
+                Color::WHITE,
+            ),
+            ItemShape::Star => {
+                let points: Vec<[f32; 2]> = (0..10)
+                    .map(|i| {
+                        let radius = if i % 2 == 0 { 6.0 } else { 2.5 };
+                        let angle = (i as f32 * 36.0 - 90.0).to_radians();
+                        [angle.cos() * radius, angle.sin() * radius]
+                    })
+                    .collect();
+                graphics::Mesh::new_polygon(ctx, mode, &points, Color::WHITE)
+            },
+        }
+    }
+}
+
+/// Shape and color of the locations of a `rand_pool`.
+#[allow(clippy::match_same_arms)]
+#[must_use]
+pub fn pool_style(pool: &str) -> (ItemShape, Color) {
+    let (shape, color) = match pool {
+        // progression
+        "Skill" | "SplitCloak" | "SplitCloakLocation" | "SplitClaw" | "CursedNail" | "Cursed" => {
+            (ItemShape::Star, 0xFFFFFF)
+        },
+        "Dreamer" => (ItemShape::Star, 0xC8A0FF),
+        "Charm" | "Notch" => (ItemShape::Diamond, 0xFF9FD0),
+        "Key" => (ItemShape::Square, 0xFFD27F),
+        "Stag" => (ItemShape::Square, 0x9AB4FF),
+        "Map" => (ItemShape::Square, 0xD8C8A0),
+        // upgrades
+        "Mask" | "Vessel" => (ItemShape::Diamond, 0xFFFFFF),
+        "Ore" => (ItemShape::Diamond, 0xA0E0FF),
+        // collectables
+        "Grub" => (ItemShape::Circle, 0x9CFF7A),
+        "Relic" => (ItemShape::Diamond, 0xE0B070),
+        "Egg" => (ItemShape::Circle, 0xB090FF),
+        "Essence_Boss" | "Root" => (ItemShape::Triangle, 0xFFF0C0),
+        "Flame" => (ItemShape::Triangle, 0xFF5050),
+        "Lore" | "PalaceLore" => (ItemShape::Square, 0xB0B0B0),
+        // filler
+        "Geo" | "Boss_Geo" | "Rock" => (ItemShape::Circle, 0xE8D070),
+        "Soul" | "PalaceSoul" => (ItemShape::Circle, 0xC8F0FF),
+        "Cocoon" => (ItemShape::Circle, 0x50C8FF),
+        _ => (ItemShape::Circle, 0xFFFF00),
+    };
+
+    (shape, Color::from_rgb_u32(color))
+}
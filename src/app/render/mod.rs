@@ -0,0 +1,3 @@
+pub mod area;
+pub mod item;
+pub mod room;
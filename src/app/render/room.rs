@@ -0,0 +1,506 @@
+use std::{
+    collections::{hash_map::Entry, HashMap},
+    convert::Into,
+};
+
+use ggez::{
+    graphics::{self, Color, DrawParam, Drawable, Font, PxScale, Rect, StrokeOptions},
+    GameResult,
+};
+use rusty_map_view::data::{item::Item, room::Room, transition::Transition, MapData, RandoData};
+
+use crate::app::{
+    render::{area::area_colors, item::pool_style},
+    settings::Settings,
+    util::{color_ext::ColorExt, rect_ext::RectExt, transform_stack::TransformStack},
+};
+
+/// How close to an item the mouse has to be to hover it, in map units.
+const ITEM_HOVER_RADIUS: f32 = 6.0;
+/// How close to a transition the mouse has to be to hover it, in map units.
+const TRANSITION_HOVER_RADIUS: f32 = 10.0;
+
+pub trait RoomExt {
+    fn fill_info_egui(
+        &self,
+        ui: &mut egui::Ui,
+        key: &str,
+        rando_data: &RandoData,
+        areas: &HashMap<String, String>,
+        show_spoilers: bool,
+    );
+
+    /// Tooltip for the mouse at `point` (see [`Room::local_point`]), with the item or
+    /// transition under it if there is one.
+    fn fill_tooltip_egui(
+        &self,
+        ui: &mut egui::Ui,
+        key: &str,
+        point: (f32, f32),
+        map_data: &MapData,
+        rando_data: &RandoData,
+        settings: &Settings,
+    );
+
+    #[allow(clippy::too_many_arguments)] // TODO
+    fn draw(
+        &mut self,
+        ctx: &mut ggez::Context,
+        transform: TransformStack,
+        key: &str,
+        rando_data: &RandoData,
+        asset_cache: &HashMap<String, graphics::Image>,
+        hovered: Option<bool>,
+        selected: Option<bool>,
+        highlight_path: Option<&[String]>,
+        settings: &Settings,
+    ) -> GameResult;
+}
+
+impl RoomExt for Room {
+    fn fill_info_egui(
+        &self,
+        ui: &mut egui::Ui,
+        key: &str,
+        rando_data: &RandoData,
+        areas: &HashMap<String, String>,
+        show_spoilers: bool,
+    ) {
+        ui.heading(self.name.as_deref().unwrap_or(key));
+        ui.label(key);
+
+        if let Some(area) = &self.area {
+            ui.label(format!(
+                "Area: {}",
+                areas.get(area).map_or(area.as_str(), String::as_str)
+            ));
+        }
+        if let Some(randomizer_area) = &self.randomizer_area {
+            ui.label(format!(
+                "Randomizer area: {}",
+                randomizer_area.replace('_', " ")
+            ));
+        }
+        if let Some(boss) = &self.boss {
+            ui.label(format!("Boss: {boss}"));
+        }
+        if let Some(stag) = &self.stag {
+            ui.label(format!("Stag station: {stag}"));
+        }
+        if !self.benches.is_empty() {
+            ui.label(format!("Benches: {}", self.benches.len()));
+        }
+
+        if !self.items.is_empty() {
+            ui.separator();
+            let checked = self
+                .items
+                .keys()
+                .filter(|name| rando_data.checked_locations.contains(*name))
+                .count();
+            ui.label(format!("Checked: {checked}/{}", self.items.len()));
+            let mut items: Vec<_> = self.items.iter().collect();
+            items.sort_by_key(|(name, _)| *name);
+            egui::Grid::new("room_items").striped(true).show(ui, |ui| {
+                for (name, item) in items {
+                    if rando_data.checked_locations.contains(name) {
+                        ui.label("\u{2714}");
+                    } else {
+                        ui.label("");
+                    }
+                    let label = ui.label(name.replace('_', " "));
+                    if show_spoilers {
+                        let placed = rando_data
+                            .item_placements
+                            .get(name)
+                            .map_or_else(|| "unknown".into(), |items| items.join(", "));
+                        label.on_hover_text(placed.replace('_', " "));
+                    }
+                    ui.label(&item.rand_pool);
+                    ui.label(item.rand_type.as_deref().unwrap_or_default());
+                    ui.label(
+                        item.geo
+                            .as_ref()
+                            .map(|g| format!("{g} geo"))
+                            .unwrap_or_default(),
+                    );
+                    ui.end_row();
+                }
+            });
+        }
+    }
+
+    fn fill_tooltip_egui(
+        &self,
+        ui: &mut egui::Ui,
+        key: &str,
+        point: (f32, f32),
+        map_data: &MapData,
+        rando_data: &RandoData,
+        settings: &Settings,
+    ) {
+        ui.strong(self.name.as_deref().unwrap_or(key));
+        if self.name.is_some() {
+            ui.label(key);
+        }
+        if let Some(area) = &self.area {
+            ui.label(
+                map_data
+                    .areas
+                    .get(area)
+                    .map_or(area.as_str(), String::as_str),
+            );
+        }
+
+        let visible = |item: &Item| !settings.hidden_pools.contains(&item.rand_pool);
+        if let Some(name) = self.item_at(point, ITEM_HOVER_RADIUS, visible) {
+            let item = &self.items[name];
+            ui.separator();
+            ui.label(format!("{} ({})", name.replace('_', " "), item.rand_pool));
+            if rando_data.checked_locations.contains(name) {
+                ui.label("\u{2714} checked");
+            }
+            if settings.show_spoilers {
+                if let Some(items) = rando_data.item_placements.get(name) {
+                    ui.label(format!("Has: {}", items.join(", ").replace('_', " ")));
+                }
+            }
+        } else if let Some(name) = self.transition_at(point, TRANSITION_HOVER_RADIUS) {
+            let transition = format!("{key}[{name}]");
+            ui.separator();
+            ui.label(format!("Transition: {name}"));
+            let visited = rando_data.visited_transitions.contains(&transition);
+            let destination = rando_data.transition_map.get(&transition);
+            match destination.and_then(|dst| Transition::get_transition_info(dst)) {
+                Some((room, tr)) if visited || settings.show_spoilers => {
+                    let room_name = map_data.rooms.get(&room).and_then(|r| r.name.as_deref());
+                    ui.label(format!("\u{2192} {} [{tr}]", room_name.unwrap_or(&room)));
+                },
+                Some(_) => {
+                    ui.label("\u{2192} ? (not visited yet)");
+                },
+                None => {
+                    ui.label("\u{2192} nowhere");
+                },
+            }
+        } else {
+            let mut items: Vec<_> = self
+                .items
+                .iter()
+                .filter(|(_, item)| !settings.hidden_pools.contains(&item.rand_pool))
+                .collect();
+            if !items.is_empty() {
+                items.sort_by_key(|(name, _)| *name);
+                ui.separator();
+                for (name, item) in items {
+                    let check = if rando_data.checked_locations.contains(name) {
+                        "\u{2714} "
+                    } else {
+                        ""
+                    };
+                    let placed = rando_data
+                        .item_placements
+                        .get(name)
+                        .filter(|_| settings.show_spoilers)
+                        .map(|items| format!(": {}", items.join(", ").replace('_', " ")))
+                        .unwrap_or_default();
+                    ui.label(format!(
+                        "{check}{} ({}){placed}",
+                        name.replace('_', " "),
+                        item.rand_pool
+                    ));
+                }
+            }
+        }
+    }
+
+    #[allow(clippy::too_many_lines)] // TODO
+    fn draw(
+        &mut self,
+        ctx: &mut ggez::Context,
+        mut transform: TransformStack,
+        key: &str,
+        rando_data: &RandoData,
+        asset_cache: &HashMap<String, graphics::Image>,
+        hovered: Option<bool>,
+        selected: Option<bool>,
+        highlight_path: Option<&[String]>,
+        settings: &Settings,
+    ) -> GameResult {
+        let bounds = self.bounds().to_ggez();
+
+        if settings.debug_show_room_origins {
+            let rect = graphics::Mesh::new_circle(
+                ctx,
+                graphics::DrawMode::Stroke(StrokeOptions::default()),
+                [0.0, 0.0],
+                2.0,
+                1.0,
+                graphics::Color::from_rgba(255, 0, 0, 255),
+            )?;
+
+            graphics::draw(ctx, &rect, &transform)?;
+        }
+
+        transform.translate(0.0, bounds.h);
+
+        let (stroke_color, fill_color) = area_colors(self.area.as_deref());
+
+        let mut alpha = if let Some(true) = selected {
+            ((ggez::timer::time_since_start(ctx).as_secs_f32() / 0.33)
+                .sin()
+                .abs())
+                * 0.2
+                + 0.8
+        } else if let Some(true) = hovered {
+            0.95
+        } else if let Some(false) = selected {
+            0.6
+        } else if let Some(false) = hovered {
+            0.7
+        } else {
+            0.8
+        };
+
+        let mut path_highlight_factor = 0.0;
+        if let Some(path) = highlight_path {
+            if let Some(i) = path
+                .iter()
+                .position(|path_tr| Transition::get_transition_info(path_tr).unwrap().0 == key)
+            {
+                let thru = ((ggez::timer::time_since_start(ctx).as_secs_f32() + i as f32) / 0.25)
+                    .sin()
+                    .max(0.25);
+                path_highlight_factor = thru;
+                alpha = alpha.max(0.8);
+            } else {
+                alpha = alpha.min(0.5);
+            }
+        }
+
+        let rect = graphics::Mesh::new_rounded_rectangle(
+            ctx,
+            graphics::DrawMode::fill(),
+            bounds,
+            5.0,
+            fill_color.lerp(&graphics::Color::from_rgba(0, 0, 0, 0), 1.0 - alpha),
+        )?;
+
+        graphics::draw(ctx, &rect, &transform)?;
+
+        let rect = graphics::Mesh::new_rounded_rectangle(
+            ctx,
+            graphics::DrawMode::stroke(2.0),
+            bounds,
+            5.0,
+            stroke_color
+                .lerp(&Color::from_rgb(255, 100, 160), path_highlight_factor)
+                .lerp(&graphics::Color::from_rgba(0, 0, 0, 0), 1.0 - alpha),
+        )?;
+
+        graphics::draw(ctx, &rect, &transform)?;
+
+        if settings.debug_show_room_origins {
+            let rect = graphics::Mesh::new_circle(
+                ctx,
+                graphics::DrawMode::Stroke(StrokeOptions::default()),
+                [0.0, 0.0],
+                2.0,
+                1.0,
+                graphics::Color::from_rgba(0, 0, 255, 255),
+            )?;
+
+            graphics::draw(ctx, &rect, &transform)?;
+        }
+
+        // room name
+        if settings.draw_room_names {
+            transform.push();
+            transform.translate(bounds.x, bounds.y + bounds.h);
+            // TODO: cache
+            graphics::Text::new(key)
+                .set_font(Font::default(), PxScale::from(12.0))
+                .draw(ctx, (&transform).into())?;
+            transform.pop();
+        }
+
+        // transitions
+
+        let transition_normal_fill = graphics::Mesh::new_polygon(
+            ctx,
+            graphics::DrawMode::fill(),
+            &[[-1.0, -12.0], [1.0, -12.0], [6.0, 4.0], [-6.0, 4.0]],
+            graphics::Color::WHITE,
+        )?;
+        let transition_door_fill = graphics::Mesh::new_polygon(
+            ctx,
+            graphics::DrawMode::fill(),
+            &[
+                [-6.0, 5.0],
+                [-6.0, -8.0],
+                [-4.0, -13.0],
+                [0.0, -15.0],
+                [4.0, -13.0],
+                [6.0, -8.0],
+                [6.0, 5.0],
+            ],
+            graphics::Color::WHITE,
+        )?;
+        let transition_normal_stroke = graphics::Mesh::new_polygon(
+            ctx,
+            graphics::DrawMode::stroke(2.0),
+            &[[-1.0, -12.0], [1.0, -12.0], [6.0, 4.0], [-6.0, 4.0]],
+            graphics::Color::WHITE,
+        )?;
+        let transition_door_stroke = graphics::Mesh::new_polygon(
+            ctx,
+            graphics::DrawMode::stroke(2.0),
+            &[
+                [-6.0, 5.0],
+                [-6.0, -8.0],
+                [-4.0, -13.0],
+                [0.0, -15.0],
+                [4.0, -13.0],
+                [6.0, -8.0],
+                [6.0, 5.0],
+            ],
+            graphics::Color::WHITE,
+        )?;
+        for (n, tr) in &self.transitions {
+            transform.push();
+            transform.translate(tr.x, -tr.y);
+
+            let transition_id = format!("{key}[{n}]");
+            let revealed = rando_data.visited_transitions.contains(&transition_id);
+
+            let scale = if revealed { 0.8 } else { 1.0 };
+            transform.scale(scale, scale);
+
+            let mut color = if revealed {
+                Color::from_rgba(150, 160, 150, 127)
+            } else {
+                Color::from_rgba(255, 255, 127, 191).lerp(
+                    &Color::WHITE,
+                    ((ggez::timer::time_since_start(ctx).as_secs_f32()) / 0.5)
+                        .sin()
+                        .abs(),
+                )
+            };
+
+            if let Some(path) = highlight_path {
+                if let Some(i) = path.iter().position(|path_tr| path_tr == &transition_id) {
+                    let thru = ((ggez::timer::time_since_start(ctx).as_secs_f32() + i as f32)
+                        / 0.25)
+                        .sin()
+                        .max(0.25);
+                    color = color.lerp(&Color::from_rgb(255, 100, 160), thru);
+                }
+            }
+
+            if n.starts_with("door") || n.starts_with("room") {
+                let param: DrawParam = Into::<DrawParam>::into(&transform).color(color);
+                graphics::draw(
+                    ctx,
+                    if revealed {
+                        &transition_door_stroke
+                    } else {
+                        &transition_door_fill
+                    },
+                    param,
+                )?;
+                // graphics::draw(ctx, &rect, &transform);
+            } else {
+                if n.starts_with("left") {
+                    transform.rotate(-90.0_f32.to_radians());
+                } else if n.starts_with("right") {
+                    transform.rotate(90.0_f32.to_radians());
+                } else if n.starts_with("bot") {
+                    transform.rotate(180.0_f32.to_radians());
+                }
+                let param: DrawParam = Into::<DrawParam>::into(&transform).color(color);
+                graphics::draw(
+                    ctx,
+                    if revealed {
+                        &transition_normal_stroke
+                    } else {
+                        &transition_normal_fill
+                    },
+                    param,
+                )?;
+                // graphics::draw(ctx, &rect, &transform);
+            }
+
+            transform.pop();
+        }
+
+        // items
+
+        let pulse = ((ggez::timer::time_since_start(ctx).as_secs_f32()) / 0.5)
+            .sin()
+            .abs();
+        let mut meshes = HashMap::new();
+        for (name, i) in &self.items {
+            if settings.hidden_pools.contains(&i.rand_pool) {
+                continue;
+            }
+
+            let (shape, color) = pool_style(&i.rand_pool);
+            if let Entry::Vacant(entry) = meshes.entry(shape) {
+                entry.insert(shape.mesh(ctx)?);
+            }
+
+            transform.push();
+            transform.translate(i.x, -i.y);
+
+            let param: DrawParam = if rando_data.checked_locations.contains(name) {
+                transform.scale(0.6, 0.6);
+                Into::<DrawParam>::into(&transform).color(Color::from_rgba(150, 150, 120, 100))
+            } else {
+                Into::<DrawParam>::into(&transform).color(color.lerp(&Color::WHITE, pulse * 0.6))
+            };
+            graphics::draw(ctx, &meshes[&shape], param)?;
+
+            transform.pop();
+        }
+
+        // benches
+
+        if let Some(img) = asset_cache.get("pin_bench") {
+            // let bench = graphics::Mesh::new_rectangle(
+            //     ctx,
+            //     graphics::DrawMode::fill(),
+            //     Rect::new(-10.0, -4.0 + 3.0, 20.0, 8.0),
+            //     graphics::Color::CYAN,
+            // )?;
+            for b in &self.benches {
+                transform.push();
+                transform.translate(b.x, -b.y);
+
+                transform.scale(0.33, 0.33);
+                transform.translate(-(img.width() as f32) / 2.0, -(img.height() as f32) / 2.0);
+
+                graphics::draw(ctx, img, &transform)?;
+
+                transform.pop();
+            }
+        } else {
+            let bench = graphics::Mesh::new_rectangle(
+                ctx,
+                graphics::DrawMode::fill(),
+                Rect::new(-10.0, -4.0 + 3.0, 20.0, 8.0),
+                graphics::Color::CYAN,
+            )?;
+            for b in &self.benches {
+                transform.push();
+                transform.translate(b.x, -b.y);
+
+                graphics::draw(ctx, &bench, &transform)?;
+                // graphics::draw(ctx, &rect, &transform);
+
+                transform.pop();
+            }
+        }
+
+        Ok(())
+    }
+}
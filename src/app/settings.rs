@@ -0,0 +1,75 @@
+use std::collections::BTreeSet;
+
+use egui::Ui;
+
+use rusty_map_view::{connection, graph::PathCosts};
+
+#[allow(clippy::struct_excessive_bools)]
+pub struct Settings {
+    pub ws_url: String,
+    pub save_path: String,
+    pub depth: u8,
+    pub draw_room_names: bool,
+    pub area_pull: f32,
+    pub draw_area_hulls: bool,
+    pub path_costs: PathCosts,
+    pub show_spoilers: bool,
+    /// `rand_pool`s whose items aren't drawn.
+    pub hidden_pools: BTreeSet<String>,
+    pub debug_show_room_origins: bool,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            ws_url: connection::DEFAULT_URL.into(),
+            save_path: String::new(),
+            depth: 2,
+            draw_room_names: true,
+            area_pull: 0.0,
+            draw_area_hulls: false,
+            path_costs: PathCosts::default(),
+            show_spoilers: false,
+            hidden_pools: BTreeSet::new(),
+            debug_show_room_origins: false,
+        }
+    }
+}
+
+impl Settings {
+    pub fn fill_debug_egui(&mut self, ui: &mut Ui) {
+        ui.add(egui::Slider::new(&mut self.depth, 0..=10).text("depth"));
+        ui.checkbox(&mut self.draw_room_names, "draw_room_names");
+        ui.add(egui::Slider::new(&mut self.area_pull, 0.0..=1.0).text("area_pull"));
+        ui.checkbox(&mut self.draw_area_hulls, "draw_area_hulls");
+        ui.add(
+            egui::Slider::new(&mut self.path_costs.transition, 0.0..=1000.0)
+                .text("path_cost_transition"),
+        );
+        ui.add(egui::Slider::new(&mut self.path_costs.door, 0.0..=1000.0).text("path_cost_door"));
+        ui.add(
+            egui::Slider::new(&mut self.path_costs.elevator, 0.0..=2000.0)
+                .text("path_cost_elevator"),
+        );
+        ui.checkbox(&mut self.debug_show_room_origins, "debug_show_room_origins");
+    }
+
+    /// A checkbox for each of `pools` to show or hide its items.
+    pub fn fill_pools_egui<'a>(&mut self, ui: &mut Ui, pools: impl IntoIterator<Item = &'a str>) {
+        egui::Grid::new("item_pools").num_columns(3).show(ui, |ui| {
+            for (i, pool) in pools.into_iter().enumerate() {
+                let mut shown = !self.hidden_pools.contains(pool);
+                if ui.checkbox(&mut shown, pool.replace('_', " ")).changed() {
+                    if shown {
+                        self.hidden_pools.remove(pool);
+                    } else {
+                        self.hidden_pools.insert(pool.into());
+                    }
+                }
+                if i % 3 == 2 {
+                    ui.end_row();
+                }
+            }
+        });
+    }
+}
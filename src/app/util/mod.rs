@@ -0,0 +1,3 @@
+pub mod color_ext;
+pub mod rect_ext;
+pub mod transform_stack;
@@ -0,0 +1,12 @@
+use ggez::graphics;
+use rusty_map_view::util::rect::Rect;
+
+pub trait RectExt {
+    fn to_ggez(&self) -> graphics::Rect;
+}
+
+impl RectExt for Rect {
+    fn to_ggez(&self) -> graphics::Rect {
+        graphics::Rect::new(self.x, self.y, self.w, self.h)
+    }
+}
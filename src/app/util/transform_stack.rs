@@ -28,9 +28,10 @@ impl TransformStack {
     }
 
     pub fn scale<T: Into<f64>>(&mut self, x: T, y: T) {
+        // z has to stay 1, a 0 would make the matrix impossible to invert
         *self.stack.last_mut().unwrap() = nalgebra_glm::scale(
             self.stack.last_mut().unwrap(),
-            &nalgebra_glm::vec3(x.into() as f32, y.into() as f32, 0.0),
+            &nalgebra_glm::vec3(x.into() as f32, y.into() as f32, 1.0),
         );
         // let prev_x = self.stack.last_mut().unwrap().scale_x;
         // let prev_y = self.stack.last_mut().unwrap().scale_y;
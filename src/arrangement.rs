@@ -0,0 +1,154 @@
+use std::{
+    collections::{BTreeSet, HashMap},
+    path::{Path, PathBuf},
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::data::RandoData;
+
+/// The room positions and pinned rooms of a seed for the local view and the world map, saved
+/// so a manual arrangement survives restarts.
+#[derive(Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct Arrangement {
+    #[serde(serialize_with = "crate::data::sorted")]
+    pub positions: HashMap<String, (f32, f32)>,
+    #[serde(default)]
+    pub pinned: BTreeSet<String>,
+    // older arrangements only have the local view
+    #[serde(default, serialize_with = "crate::data::sorted")]
+    pub world_positions: HashMap<String, (f32, f32)>,
+    #[serde(default)]
+    pub world_pinned: BTreeSet<String>,
+}
+
+impl Arrangement {
+    #[must_use]
+    pub fn from_rando_data(rando_data: &RandoData) -> Self {
+        Self {
+            positions: rando_data.room_positions.clone(),
+            pinned: rando_data.pinned_rooms.iter().cloned().collect(),
+            world_positions: rando_data.world_positions.clone(),
+            world_pinned: rando_data.world_pinned_rooms.iter().cloned().collect(),
+        }
+    }
+
+    /// Restores the arrangement, with `current_room` at the origin of the local view like the
+    /// viewer expects.
+    pub fn apply(self, rando_data: &mut RandoData, current_room: &str) {
+        rando_data.room_positions = self.positions;
+        rando_data.pinned_rooms = self.pinned.into_iter().collect();
+        rando_data.world_positions = self.world_positions;
+        rando_data.world_pinned_rooms = self.world_pinned.into_iter().collect();
+        rando_data.recenter(current_room);
+    }
+
+    /// `<data dir>/rusty-map-view/arrangements`, `None` if the platform doesn't have one.
+    #[must_use]
+    pub fn default_dir() -> Option<PathBuf> {
+        dirs::data_dir().map(|dir| dir.join("rusty-map-view").join("arrangements"))
+    }
+
+    #[must_use]
+    pub fn path(dir: &Path, seed: i64) -> PathBuf {
+        dir.join(format!("{seed}.json"))
+    }
+
+    /// Reads the arrangement saved for `seed`, `Ok(None)` if there isn't one yet.
+    pub fn load(dir: &Path, seed: i64) -> Result<Option<Self>, String> {
+        let path = Self::path(dir, seed);
+        let source = match std::fs::read_to_string(&path) {
+            Ok(source) => source,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(format!("{}: {e}", path.display())),
+        };
+        serde_json::from_str(&source)
+            .map(Some)
+            .map_err(|e| format!("{}: {e}", path.display()))
+    }
+
+    pub fn save(&self, dir: &Path, seed: i64) -> Result<(), String> {
+        std::fs::create_dir_all(dir).map_err(|e| format!("{}: {e}", dir.display()))?;
+        let path = Self::path(dir, seed);
+        let json = serde_json::to_string(self).map_err(|e| e.to_string())?;
+        std::fs::write(&path, json).map_err(|e| format!("{}: {e}", path.display()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("rusty-map-view-{name}-{}", std::process::id()))
+    }
+
+    fn rando_data() -> RandoData {
+        RandoData {
+            room_positions: HashMap::from([
+                ("Town".into(), (10.0, 20.0)),
+                ("Crossroads_01".into(), (110.5, -40.25)),
+            ]),
+            pinned_rooms: ["Crossroads_01".into()].into(),
+            world_positions: HashMap::from([
+                ("Town".into(), (-300.0, 0.0)),
+                ("Crossroads_01".into(), (-200.0, 60.0)),
+                ("Tutorial_01".into(), (-500.0, 10.0)),
+            ]),
+            world_pinned_rooms: ["Tutorial_01".into()].into(),
+            ..RandoData::default()
+        }
+    }
+
+    #[test]
+    fn save_and_load() {
+        let dir = temp_dir("save-and-load");
+        let arrangement = Arrangement::from_rando_data(&rando_data());
+        arrangement.save(&dir, -42).unwrap();
+        let loaded = Arrangement::load(&dir, -42);
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(loaded, Ok(Some(arrangement)));
+    }
+
+    #[test]
+    fn load_missing() {
+        let dir = temp_dir("load-missing");
+        assert_eq!(Arrangement::load(&dir, 1), Ok(None));
+    }
+
+    #[test]
+    fn apply_recenters_the_local_view() {
+        let original = rando_data();
+        let mut rando_data = RandoData::default();
+        Arrangement::from_rando_data(&original).apply(&mut rando_data, "Town");
+
+        assert_eq!(rando_data.room_positions["Town"], (0.0, 0.0));
+        assert_eq!(rando_data.room_positions["Crossroads_01"], (100.5, -60.25));
+        assert_eq!(rando_data.pinned_rooms, original.pinned_rooms);
+        // the world map isn't relative to the current room
+        assert_eq!(rando_data.world_positions, original.world_positions);
+        assert_eq!(rando_data.world_pinned_rooms, original.world_pinned_rooms);
+    }
+
+    #[test]
+    fn load_without_world_map() {
+        let dir = temp_dir("load-without-world-map");
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(
+            Arrangement::path(&dir, 7),
+            r#"{"positions":{"Town":[1.0,2.0]},"pinned":["Town"]}"#,
+        )
+        .unwrap();
+        let loaded = Arrangement::load(&dir, 7);
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        let loaded = loaded.unwrap().unwrap();
+        assert_eq!(
+            loaded.positions,
+            HashMap::from([("Town".into(), (1.0, 2.0))])
+        );
+        assert!(loaded.world_positions.is_empty());
+        assert!(loaded.world_pinned.is_empty());
+    }
+}
@@ -0,0 +1,122 @@
+#![deny(clippy::all)]
+#![allow(clippy::cargo)]
+#![warn(clippy::pedantic)]
+#![allow(clippy::missing_errors_doc)]
+#![allow(clippy::missing_panics_doc)]
+
+//! Prints the layout of a save as JSON. Only needs the library, so it builds without the `app`
+//! feature (and without ggez's graphics and audio dependencies).
+
+use std::{collections::BTreeMap, path::PathBuf};
+
+use rusty_map_view::{
+    data,
+    layout::{self, LayoutOptions},
+    save,
+    util::flags::Flags,
+};
+
+const USAGE: &str = "\
+Usage: rusty-map-layout (--save <FILE> | --vanilla) [OPTIONS]
+
+Lays out the visited rooms of a save and prints the room positions as JSON.
+The same save and options always give the same positions.
+
+Options:
+    --save <FILE>            Save file to lay out (modded .json save, or an exported loadSave/PolymorphicModData)
+    --vanilla                Lay out every room by the vanilla map instead of the visited rooms of a save
+    --mapdata <FILE>         Load map data from FILE instead of the built in copy
+    --room <ROOM>            Room to put at the origin [default: the room the save is in]
+    --depth <N>              How many visited transitions away from the room to lay out [default: 2]
+    --iterations <N>         Maximum number of layout steps [default: 600]
+    --seed <N>               Seed for the starting positions [default: 0]
+    --area-pull <STRENGTH>   How strongly rooms are pulled towards the rest of their area, 0 to 1 [default: 0]
+    -h, --help               Print this message";
+
+#[derive(Default)]
+struct Args {
+    mapdata: Option<PathBuf>,
+    save: Option<PathBuf>,
+    room: Option<String>,
+    vanilla: bool,
+    layout: LayoutOptions,
+}
+
+impl Args {
+    /// `Ok(None)` if the usage should be printed.
+    fn parse(args: impl IntoIterator<Item = String>) -> Result<Option<Self>, String> {
+        let mut parsed = Args::default();
+
+        let mut flags = Flags::new(args);
+        while let Some(flag) = flags.next_flag() {
+            match flag.as_str() {
+                "-h" | "--help" => return Ok(None),
+                "--mapdata" => parsed.mapdata = Some(flags.value("--mapdata")?.into()),
+                "--save" => parsed.save = Some(flags.value("--save")?.into()),
+                "--room" => parsed.room = Some(flags.value("--room")?),
+                "--vanilla" => parsed.vanilla = true,
+                "--depth" => parsed.layout.depth = flags.parse("--depth")?,
+                "--iterations" => parsed.layout.iterations = flags.parse("--iterations")?,
+                "--seed" => parsed.layout.seed = flags.parse("--seed")?,
+                "--area-pull" => {
+                    parsed.layout.area_pull = Some(flags.parse("--area-pull")?)
+                        .filter(|s: &f32| (0.0..=1.0).contains(s))
+                        .ok_or("Invalid value for --area-pull")?;
+                },
+                _ => return Err(format!("Unknown argument '{flag}'")),
+            }
+        }
+
+        if parsed.save.is_none() && !parsed.vanilla {
+            return Err("Needs a --save or --vanilla to lay out".into());
+        }
+
+        Ok(Some(parsed))
+    }
+}
+
+fn export_layout(args: &Args) -> Result<String, String> {
+    let map_data = match &args.mapdata {
+        Some(path) => data::read_mapdata(path)?,
+        None => data::load_mapdata(data::EMBEDDED_MAPDATA)?,
+    };
+
+    let positions = if args.vanilla {
+        layout::vanilla_layout(&map_data, &args.layout)
+    } else {
+        let path = args.save.as_ref().expect("checked by Args::parse");
+        let save = save::load_save_file(path, &map_data)?;
+
+        let root = args.room.as_ref().unwrap_or(&save.current_room);
+        if !map_data.rooms.contains_key(root) {
+            return Err(format!("Unknown room '{root}'"));
+        }
+
+        layout::layout(&map_data, &save.rando_data, root, &args.layout)
+    };
+
+    let sorted: BTreeMap<_, _> = positions.into_iter().collect();
+    serde_json::to_string_pretty(&sorted).map_err(|e| e.to_string())
+}
+
+fn main() {
+    let args = match Args::parse(std::env::args().skip(1)) {
+        Ok(Some(args)) => args,
+        Ok(None) => {
+            println!("{USAGE}");
+            return;
+        },
+        Err(e) => {
+            eprintln!("{e}\n\n{USAGE}");
+            std::process::exit(2);
+        },
+    };
+
+    match export_layout(&args) {
+        Ok(json) => println!("{json}"),
+        Err(e) => {
+            eprintln!("error: {e}");
+            std::process::exit(1);
+        },
+    }
+}
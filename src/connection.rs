@@ -0,0 +1,255 @@
+use std::{
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        mpsc::{self, RecvTimeoutError},
+        Arc, Mutex,
+    },
+    thread::JoinHandle,
+    time::{Duration, Instant},
+};
+
+use parity_ws::{CloseCode, Handler, Handshake, Message, Sender};
+
+use crate::{
+    inbox::{self, Inbox, InboxSender},
+    recording::Recorder,
+};
+
+pub const DEFAULT_URL: &str = "ws://localhost:7900/ws";
+
+const INITIAL_BACKOFF: Duration = Duration::from_millis(500);
+const MAX_BACKOFF: Duration = Duration::from_secs(10);
+/// How long [`Connection::shutdown`] waits for the listener thread, which can be stuck resolving
+/// or connecting to an unreachable host.
+const SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(1);
+
+#[derive(Clone, Debug)]
+pub enum ConnectionStatus {
+    Connecting {
+        attempt: u32,
+    },
+    Connected,
+    Waiting {
+        attempt: u32,
+        retry_at: Instant,
+        error: Option<String>,
+    },
+    Closed,
+}
+
+struct Shared {
+    url: Mutex<String>,
+    status: Mutex<ConnectionStatus>,
+    out: Mutex<Option<Sender>>,
+    shutdown: AtomicBool,
+    recorder: Option<Mutex<Recorder>>,
+}
+
+impl Shared {
+    fn set_status(&self, status: ConnectionStatus) {
+        *self.status.lock().unwrap() = status;
+    }
+}
+
+/// Owns the websocket listener thread.
+/// The thread keeps reconnecting (with exponential backoff) until [`Connection::shutdown`] is called,
+/// so the viewer can be started before the game and survives the game restarting.
+pub struct Connection {
+    shared: Arc<Shared>,
+    wake: mpsc::Sender<()>,
+    listen_thread: Option<JoinHandle<()>>,
+    /// Disconnects when the listener thread is done.
+    done: mpsc::Receiver<()>,
+}
+
+impl Connection {
+    #[must_use]
+    pub fn start(url: String, recorder: Option<Recorder>) -> (Self, Inbox) {
+        let (send, recv) = inbox::inbox();
+        let (wake, recv_wake) = mpsc::channel();
+        let (done_send, done) = mpsc::channel::<()>();
+
+        let shared = Arc::new(Shared {
+            url: Mutex::new(url),
+            status: Mutex::new(ConnectionStatus::Connecting { attempt: 1 }),
+            out: Mutex::new(None),
+            shutdown: AtomicBool::new(false),
+            recorder: recorder.map(Mutex::new),
+        });
+
+        let thread_shared = Arc::clone(&shared);
+        let listen_thread = std::thread::spawn(move || {
+            let shared = thread_shared;
+            let _done = done_send;
+            let mut backoff = INITIAL_BACKOFF;
+            let mut attempt = 1;
+
+            while !shared.shutdown.load(Ordering::SeqCst) {
+                // any wakeups queued before this attempt are already handled by it
+                while recv_wake.try_recv().is_ok() {}
+
+                let url = shared.url.lock().unwrap().clone();
+                shared.set_status(ConnectionStatus::Connecting { attempt });
+                println!("Connecting to {url}...");
+
+                let connected = Arc::new(AtomicBool::new(false));
+                let last_error = Arc::new(Mutex::new(None));
+                let res = parity_ws::connect(url.as_str(), |out| {
+                    if shared.shutdown.load(Ordering::SeqCst) {
+                        out.close(CloseCode::Normal).unwrap();
+                    }
+                    *shared.out.lock().unwrap() = Some(out);
+
+                    Client {
+                        shared: Arc::clone(&shared),
+                        send: send.clone(),
+                        connected: Arc::clone(&connected),
+                        last_error: Arc::clone(&last_error),
+                    }
+                });
+                *shared.out.lock().unwrap() = None;
+
+                if shared.shutdown.load(Ordering::SeqCst) {
+                    break;
+                }
+
+                let error = match res {
+                    Ok(()) => last_error.lock().unwrap().take(),
+                    Err(e) => Some(e.to_string()),
+                };
+
+                if connected.load(Ordering::SeqCst) {
+                    println!("Disconnected from {url}");
+                    backoff = INITIAL_BACKOFF;
+                    attempt = 1;
+                } else {
+                    attempt += 1;
+                }
+
+                shared.set_status(ConnectionStatus::Waiting {
+                    attempt,
+                    retry_at: Instant::now() + backoff,
+                    error,
+                });
+
+                match recv_wake.recv_timeout(backoff) {
+                    Ok(()) => {
+                        // url changed or reconnect requested, retry right away
+                        backoff = INITIAL_BACKOFF;
+                        attempt = 1;
+                    },
+                    Err(RecvTimeoutError::Timeout) => {
+                        backoff = (backoff * 2).min(MAX_BACKOFF);
+                    },
+                    Err(RecvTimeoutError::Disconnected) => break,
+                }
+            }
+
+            shared.set_status(ConnectionStatus::Closed);
+        });
+
+        (
+            Self {
+                shared,
+                wake,
+                listen_thread: Some(listen_thread),
+                done,
+            },
+            recv,
+        )
+    }
+
+    #[must_use]
+    pub fn status(&self) -> ConnectionStatus {
+        self.shared.status.lock().unwrap().clone()
+    }
+
+    #[must_use]
+    pub fn url(&self) -> String {
+        self.shared.url.lock().unwrap().clone()
+    }
+
+    /// Drops the current connection (if any) and reconnects to `url`.
+    pub fn set_url(&self, url: String) {
+        *self.shared.url.lock().unwrap() = url;
+        self.reconnect();
+    }
+
+    pub fn reconnect(&self) {
+        // the thread might have exited already if we're shutting down
+        let _ = self.wake.send(());
+        if let Some(out) = self.shared.out.lock().unwrap().as_ref() {
+            // a connection that isn't open yet can't be closed, stop connecting instead
+            let _ = if matches!(self.status(), ConnectionStatus::Connected) {
+                out.close(CloseCode::Normal)
+            } else {
+                out.shutdown()
+            };
+        }
+    }
+
+    /// Stops the listener thread. It's left behind if it doesn't stop within
+    /// [`SHUTDOWN_TIMEOUT`], e.g. while resolving a host, so closing never hangs.
+    pub fn shutdown(&mut self) {
+        if let Some(listen_thread) = self.listen_thread.take() {
+            println!("Closing connection...");
+            self.shared.shutdown.store(true, Ordering::SeqCst);
+            self.reconnect();
+            match self.done.recv_timeout(SHUTDOWN_TIMEOUT) {
+                Err(RecvTimeoutError::Timeout) => {
+                    println!("Connection thread didn't stop, leaving it");
+                },
+                Ok(()) | Err(RecvTimeoutError::Disconnected) => {
+                    listen_thread.join().unwrap();
+                    println!("Done");
+                },
+            }
+        }
+    }
+}
+
+impl Drop for Connection {
+    fn drop(&mut self) {
+        self.shutdown();
+    }
+}
+
+struct Client {
+    shared: Arc<Shared>,
+    send: InboxSender,
+    connected: Arc<AtomicBool>,
+    last_error: Arc<Mutex<Option<String>>>,
+}
+
+impl Handler for Client {
+    fn on_open(&mut self, _shake: Handshake) -> parity_ws::Result<()> {
+        println!("Connected");
+        self.connected.store(true, Ordering::SeqCst);
+        self.shared.set_status(ConnectionStatus::Connected);
+        Ok(())
+    }
+
+    fn on_message(&mut self, msg: Message) -> parity_ws::Result<()> {
+        if let Ok(data) = msg.into_text() {
+            println!("recv {} bytes", data.len());
+            match json::parse(&data) {
+                Ok(json) => {
+                    if let Some(recorder) = &self.shared.recorder {
+                        if let Err(e) = recorder.lock().unwrap().record(&json) {
+                            eprintln!("Failed to record message: {e}");
+                        }
+                    }
+                    // receiver is gone if the app is closing, nothing to do
+                    let _ = self.send.send(json);
+                },
+                Err(err) => eprintln!("json::parse: {err}"),
+            }
+        }
+
+        Ok(())
+    }
+
+    fn on_error(&mut self, err: parity_ws::Error) {
+        *self.last_error.lock().unwrap() = Some(err.to_string());
+    }
+}
@@ -1,22 +1,73 @@
-use json::JsonValue;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Number, Value};
 
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(from = "RawItem", into = "RawItem")]
 pub struct Item {
     pub x: f32,
     pub y: f32,
-    //geo
-    //randAction
+    /// `false` if the map data doesn't have coordinates for this item, `x`/`y` are 0 then.
+    pub has_position: bool,
+    /// `x`/`y` as they're written in the map data, see [`super::MapData::to_json`].
+    pub exact_position: Option<(Number, Number)>,
+    pub geo: Option<String>,
+    pub rand_action: Option<String>,
     pub rand_pool: String,
-    //randType
+    pub rand_type: Option<String>,
+    /// Fields this doesn't know about, kept so they're written back.
+    pub extra: Map<String, Value>,
 }
 
-impl TryFrom<&JsonValue> for Item {
-    type Error = String;
+/// Item as it is stored in the map data, with optional coordinates.
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RawItem {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    geo: Option<String>,
+    // `null` for some items, always written out
+    rand_action: Option<String>,
+    rand_pool: String,
+    rand_type: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    x: Option<Number>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    y: Option<Number>,
+    #[serde(flatten)]
+    extra: Map<String, Value>,
+}
+
+impl From<RawItem> for Item {
+    fn from(raw: RawItem) -> Self {
+        let position = raw.x.zip(raw.y).map(|(x, y)| super::read_position(x, y));
+        let (x, y) = position.as_ref().map_or((0.0, 0.0), |(pos, _)| *pos);
+        Self {
+            x,
+            y,
+            has_position: position.is_some(),
+            exact_position: position.map(|(_, exact)| exact),
+            geo: raw.geo,
+            rand_action: raw.rand_action,
+            rand_pool: raw.rand_pool,
+            rand_type: raw.rand_type,
+            extra: raw.extra,
+        }
+    }
+}
 
-    fn try_from(json: &JsonValue) -> Result<Self, Self::Error> {
-        Ok(Self {
-            x: json["x"].as_f32().unwrap_or(0.0),
-            y: json["y"].as_f32().unwrap_or(0.0),
-            rand_pool: json["randPool"].as_str().unwrap().into(),
-        })
+impl From<Item> for RawItem {
+    fn from(item: Item) -> Self {
+        let (x, y) = item
+            .has_position
+            .then(|| super::write_position((item.x, item.y), item.exact_position.as_ref()))
+            .unzip();
+        Self {
+            geo: item.geo,
+            rand_action: item.rand_action,
+            rand_pool: item.rand_pool,
+            rand_type: item.rand_type,
+            x,
+            y,
+            extra: item.extra,
+        }
     }
 }
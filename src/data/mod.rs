@@ -1,39 +1,338 @@
-use std::collections::{HashMap, HashSet};
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap, HashSet},
+    path::Path,
+};
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Number, Value};
 
 use self::room::Room;
 
 pub mod item;
 pub mod room;
 pub mod transition;
+pub mod validate;
+pub mod watcher;
+
+pub const EMBEDDED_MAPDATA: &str = include_str!("../../res/mapdata.json");
 
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct MapData {
+    #[serde(serialize_with = "sorted")]
     pub areas: HashMap<String, String>,
+    #[serde(serialize_with = "sorted")]
     pub rooms: HashMap<String, Room>,
+    /// Fields this doesn't know about, kept so [`MapData::to_json`] writes them back.
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+/// Serializes a map with sorted keys so the output is stable.
+#[allow(clippy::implicit_hasher)]
+pub fn sorted<V: Serialize, S: serde::Serializer>(
+    map: &HashMap<String, V>,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    map.iter().collect::<BTreeMap<_, _>>().serialize(serializer)
+}
+
+/// A coordinate of the map data as an `f32`.
+fn read_coord(coord: &Number) -> f32 {
+    coord.as_f64().unwrap_or_default() as f32
+}
+
+/// Reads a position of the map data, keeping the numbers as they were written (`129`,
+/// `133.0`, `81.50000081923`) for [`write_position`].
+fn read_position(x: Number, y: Number) -> ((f32, f32), (Number, Number)) {
+    ((read_coord(&x), read_coord(&y)), (x, y))
 }
 
+/// The numbers to write for the position `(x, y)`: the ones it was read from if it didn't
+/// move, otherwise the shortest ones that read back as the same `f32`s.
+fn write_position((x, y): (f32, f32), exact: Option<&(Number, Number)>) -> (Number, Number) {
+    let coord = |value: f32, exact: Option<&Number>| match exact {
+        Some(n) if read_coord(n).to_bits() == value.to_bits() => n.clone(),
+        // NaN and infinity aren't JSON numbers
+        _ => value
+            .to_string()
+            .parse()
+            .unwrap_or_else(|_| Number::from(0)),
+    };
+    (
+        coord(x, exact.map(|(x, _)| x)),
+        coord(y, exact.map(|(_, y)| y)),
+    )
+}
+
+impl MapData {
+    /// Serializes back to the same layout as `mapdata.json` (sorted keys, tab indented).
+    /// Unchanged coordinates and unknown fields are written exactly as they were read, only
+    /// rooms without benches, items or transitions get them written out empty.
+    pub fn to_json(&self) -> Result<String, String> {
+        // through a `Value` so the unknown fields get sorted in with the others
+        let value = serde_json::to_value(self).map_err(|e| e.to_string())?;
+        let mut out = Vec::new();
+        let formatter = serde_json::ser::PrettyFormatter::with_indent(b"\t");
+        let mut ser = serde_json::Serializer::with_formatter(&mut out, formatter);
+        value.serialize(&mut ser).map_err(|e| e.to_string())?;
+
+        String::from_utf8(out).map_err(|e| e.to_string())
+    }
+
+    /// Every `rand_pool` of the items in the map data.
+    #[must_use]
+    pub fn item_pools(&self) -> BTreeSet<&str> {
+        self.rooms
+            .values()
+            .flat_map(|room| room.items.values())
+            .map(|item| item.rand_pool.as_str())
+            .collect()
+    }
+}
+
+#[derive(Default)]
 pub struct RandoData {
     pub transition_map: HashMap<String, String>,
+    /// Transitions whose destination in `transition_map` comes from the randomizer, the rest
+    /// go to their vanilla destination from the map data.
+    pub randomized_transitions: HashSet<String>,
     pub visited_transitions: HashSet<String>,
     pub room_positions: HashMap<String, (f32, f32)>,
+    /// Positions for the world map, which aren't relative to the current room.
+    pub world_positions: HashMap<String, (f32, f32)>,
+    /// Positions for the vanilla map, a copy of [`crate::layout::VanillaLayout`] rooms can be
+    /// dragged around in.
+    pub vanilla_positions: HashMap<String, (f32, f32)>,
+    /// Rooms the user moved by hand in the local view, the layout leaves them where they are.
+    pub pinned_rooms: HashSet<String>,
+    /// Like `pinned_rooms`, for the world map.
+    pub world_pinned_rooms: HashSet<String>,
+    /// Locations whose item was picked up, named like the items of [`room::Room`].
+    pub checked_locations: HashSet<String>,
+    /// The items placed at each location, from the randomizer context. Spoilers!
+    pub item_placements: HashMap<String, Vec<String>>,
+}
+
+impl RandoData {
+    /// The unrandomized game with every transition visited.
+    #[must_use]
+    pub fn vanilla(map_data: &MapData) -> Self {
+        let mut rando_data = Self::default();
+        rando_data.fill_vanilla_transitions(map_data);
+        rando_data.visited_transitions = rando_data
+            .transition_map
+            .iter()
+            .flat_map(|(src, dst)| [src.clone(), dst.clone()])
+            .collect();
+        rando_data
+    }
+
+    /// Sets the destination of every transition that isn't randomized to its vanilla one from
+    /// `map_data`, dropping the ones the map data doesn't have (anymore).
+    pub fn fill_vanilla_transitions(&mut self, map_data: &MapData) {
+        let randomized = &self.randomized_transitions;
+        self.transition_map
+            .retain(|src, _| randomized.contains(src));
+        for (id, room) in &map_data.rooms {
+            for (tr_id, tr) in &room.transitions {
+                let src = format!("{id}[{tr_id}]");
+                if let (Some(to), false) = (&tr.to, randomized.contains(&src)) {
+                    self.transition_map.insert(src, to.clone());
+                }
+            }
+        }
+    }
+
+    /// Drops state that refers to rooms which no longer exist, after the map data was reloaded.
+    pub fn retain_rooms(&mut self, map_data: &MapData) {
+        self.room_positions
+            .retain(|room, _| map_data.rooms.contains_key(room));
+        self.world_positions
+            .retain(|room, _| map_data.rooms.contains_key(room));
+        // rooms may have moved between areas, so this is copied again once the new map data is
+        // laid out
+        self.vanilla_positions.clear();
+        self.pinned_rooms
+            .retain(|room| map_data.rooms.contains_key(room));
+        self.world_pinned_rooms
+            .retain(|room| map_data.rooms.contains_key(room));
+        self.fill_vanilla_transitions(map_data);
+    }
+
+    /// Moves every room so `room` ends up at the origin, keeping the arrangement.
+    /// Returns how far everything moved.
+    pub fn recenter(&mut self, room: &str) -> (f32, f32) {
+        let Some(&(x, y)) = self.room_positions.get(room) else {
+            return (0.0, 0.0);
+        };
+        for (room_x, room_y) in self.room_positions.values_mut() {
+            *room_x -= x;
+            *room_y -= y;
+        }
+        (-x, -y)
+    }
+}
+
+pub fn read_mapdata(path: impl AsRef<Path>) -> Result<MapData, String> {
+    let path = path.as_ref();
+    let source = std::fs::read_to_string(path).map_err(|e| format!("{}: {e}", path.display()))?;
+    load_mapdata(&source).map_err(|e| format!("{}: {e}", path.display()))
 }
 
 pub fn load_mapdata(json_source: &str) -> Result<MapData, String> {
-    let json = json::parse(json_source).map_err(|je| je.to_string())?;
+    serde_json::from_str(json_source).map_err(|e| e.to_string())
+}
 
-    let areas: HashMap<String, String> = json["areas"]
-        .entries()
-        .map(|(k, v)| (k.into(), v.as_str().unwrap().into()))
-        .collect();
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn to_json_round_trips() {
+        let map_data = load_mapdata(EMBEDDED_MAPDATA).unwrap();
+        let json = map_data.to_json().unwrap();
+        let original: Value = serde_json::from_str(EMBEDDED_MAPDATA).unwrap();
+        let written: Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(written, original);
+    }
 
-    let rooms: HashMap<String, Room> = json["rooms"]
-        .entries()
-        .map(|(k, v)| {
-            Ok((
-                k.into(),
-                Room::try_from(v).map_err(|s| format!("{k}: {s}"))?,
-            ))
-        })
-        .collect::<Result<_, String>>()?;
-
-    Ok(MapData { areas, rooms })
+    #[test]
+    fn to_json_keeps_numbers_and_unknown_fields() {
+        let mut map_data = load_mapdata(
+            r#"{
+                "areas": {},
+                "rooms": {
+                    "A": {
+                        "benches": [{ "x": 1, "y": 2.0, "kind": "Bench" }],
+                        "transitions": {
+                            "right1": { "to": null, "x": 81.50000081923, "y": 129, "gate": 1 },
+                            "left1": { "to": null, "x": 9.952390000000001, "y": 0.5 }
+                        },
+                        "notes": ["hi"]
+                    }
+                },
+                "version": 2
+            }"#,
+        )
+        .unwrap();
+        // moved positions are written as short as they can be
+        map_data
+            .rooms
+            .get_mut("A")
+            .unwrap()
+            .transitions
+            .get_mut("left1")
+            .unwrap()
+            .x = 0.1;
+
+        let written: Value = serde_json::from_str(&map_data.to_json().unwrap()).unwrap();
+        let expected: Value = serde_json::from_str(
+            r#"{
+                "areas": {},
+                "rooms": {
+                    "A": {
+                        "benches": [{ "x": 1, "y": 2.0, "kind": "Bench" }],
+                        "transitions": {
+                            "right1": { "to": null, "x": 81.50000081923, "y": 129, "gate": 1 },
+                            "left1": { "to": null, "x": 0.1, "y": 0.5 }
+                        },
+                        "notes": ["hi"]
+                    }
+                },
+                "version": 2
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(written, expected);
+        // the number types have to match too, not just compare equal
+        assert_eq!(written.to_string(), expected.to_string());
+    }
+
+    #[test]
+    fn to_json_keeps_missing_and_empty_fields_apart() {
+        let json = r#"{
+            "areas": {},
+            "rooms": {
+                "Empty": { "benches": [], "items": {}, "transitions": {} },
+                "Missing": { "name": "Godhome" },
+                "Moved": {}
+            }
+        }"#;
+        let mut map_data = load_mapdata(json).unwrap();
+        let missing = &map_data.rooms["Missing"];
+        assert!(missing.benches.is_empty() && missing.items.is_empty());
+        assert!(missing.missing.benches && missing.missing.transitions);
+        assert!(!map_data.rooms["Empty"].missing.items);
+
+        let written: Value = serde_json::from_str(&map_data.to_json().unwrap()).unwrap();
+        let original: Value = serde_json::from_str(json).unwrap();
+        assert_eq!(written, original);
+
+        // a field that was missing is written once it has something in it
+        let bench = serde_json::from_str(r#"{ "x": 1, "y": 2 }"#).unwrap();
+        map_data.rooms.get_mut("Moved").unwrap().benches.push(bench);
+        let written: Value = serde_json::from_str(&map_data.to_json().unwrap()).unwrap();
+        assert_eq!(
+            written["rooms"]["Moved"],
+            serde_json::json!({ "benches": [{ "x": 1, "y": 2 }] })
+        );
+    }
+
+    #[test]
+    fn reload_rebuilds_vanilla_transitions() {
+        let map_data = load_mapdata(
+            r#"{
+                "areas": {},
+                "rooms": {
+                    "A": { "transitions": {
+                        "right1": { "to": "B[left1]" },
+                        "right2": { "to": "B[left2]" }
+                    } },
+                    "B": { "transitions": {
+                        "left1": { "to": "A[right1]" },
+                        "left2": { "to": "A[right2]" }
+                    } },
+                    "C": { "transitions": { "left1": { "to": "B[left1]" } } }
+                }
+            }"#,
+        )
+        .unwrap();
+        let mut rando_data = RandoData::default();
+        rando_data
+            .transition_map
+            .insert("A[right2]".into(), "C[left1]".into());
+        rando_data.randomized_transitions.insert("A[right2]".into());
+        rando_data.fill_vanilla_transitions(&map_data);
+        assert_eq!(rando_data.transition_map.len(), 5);
+
+        // B[left1] was fixed and C removed
+        let reloaded = load_mapdata(
+            r#"{
+                "areas": {},
+                "rooms": {
+                    "A": { "transitions": {
+                        "right1": { "to": "B[left1]" },
+                        "right2": { "to": "B[left2]" }
+                    } },
+                    "B": { "transitions": {
+                        "left1": { "to": "A[right2]" },
+                        "left2": { "to": "A[right2]" }
+                    } }
+                }
+            }"#,
+        )
+        .unwrap();
+        rando_data.retain_rooms(&reloaded);
+
+        let expected: HashMap<String, String> = [
+            ("A[right1]", "B[left1]"),
+            ("A[right2]", "C[left1]"),
+            ("B[left1]", "A[right2]"),
+            ("B[left2]", "A[right2]"),
+        ]
+        .into_iter()
+        .map(|(src, dst)| (src.into(), dst.into()))
+        .collect();
+        assert_eq!(rando_data.transition_map, expected);
+    }
 }
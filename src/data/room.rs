@@ -1,29 +1,244 @@
-use std::{collections::HashMap, convert::Into};
-
-use ggez::{
-    graphics::{self, Color, DrawParam, Drawable, Font, PxScale, Rect, StrokeOptions},
-    GameResult,
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    sync::OnceLock,
 };
-use json::JsonValue;
 
-use crate::{
-    settings::Settings,
-    util::{color_ext::ColorExt, transform_stack::TransformStack},
-};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Number, Value};
 
-use super::{item::Item, transition::Transition, RandoData};
+use crate::util::rect::Rect;
 
+use super::{item::Item, transition::Transition};
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(from = "RawRoom", into = "RawRoom")]
 pub struct Room {
     pub area: Option<String>,
-    pub benches: Vec<(f32, f32)>,
+    pub benches: Vec<Bench>,
+    pub boss: Option<String>,
     pub items: HashMap<String, Item>,
     pub name: Option<String>,
     pub randomizer_area: Option<String>,
     pub split_room: Option<Vec<Vec<String>>>,
+    /// Name of the stag station in this room.
+    pub stag: Option<String>,
     pub transitions: HashMap<String, Transition>,
+    /// Which of `benches`, `items` and `transitions` the map data leaves out, so they're left
+    /// out again instead of being written empty.
+    pub missing: MissingFields,
+    /// Fields this doesn't know about, kept so they're written back.
+    pub extra: Map<String, Value>,
+    /// Cache for [`Room::bounds`], filled the first time it's called. Leave it empty when
+    /// making a room (`..Room::default()`).
+    pub bounds_cache: OnceLock<Rect>,
+}
+
+/// See [`Room::missing`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct MissingFields {
+    pub benches: bool,
+    pub items: bool,
+    pub transitions: bool,
+}
+
+/// Room as it is stored in the map data.
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RawRoom {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    area: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    benches: Option<Vec<Bench>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    boss: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    items: Option<BTreeMap<String, Item>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    name: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    randomizer_area: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    split_room: Option<Vec<Vec<String>>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    stag: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    transitions: Option<BTreeMap<String, Transition>>,
+    #[serde(flatten)]
+    extra: Map<String, Value>,
+}
+
+impl From<RawRoom> for Room {
+    fn from(raw: RawRoom) -> Self {
+        Self {
+            missing: MissingFields {
+                benches: raw.benches.is_none(),
+                items: raw.items.is_none(),
+                transitions: raw.transitions.is_none(),
+            },
+            area: raw.area,
+            benches: raw.benches.unwrap_or_default(),
+            boss: raw.boss,
+            items: raw.items.unwrap_or_default().into_iter().collect(),
+            name: raw.name,
+            randomizer_area: raw.randomizer_area,
+            split_room: raw.split_room,
+            stag: raw.stag,
+            transitions: raw.transitions.unwrap_or_default().into_iter().collect(),
+            extra: raw.extra,
+            bounds_cache: OnceLock::new(),
+        }
+    }
+}
+
+impl From<Room> for RawRoom {
+    fn from(room: Room) -> Self {
+        // only left out if they're still empty, e.g. a room could have gotten a bench
+        let missing = room.missing;
+        Self {
+            area: room.area,
+            benches: (!missing.benches || !room.benches.is_empty()).then_some(room.benches),
+            boss: room.boss,
+            items: (!missing.items || !room.items.is_empty())
+                .then(|| room.items.into_iter().collect()),
+            name: room.name,
+            randomizer_area: room.randomizer_area,
+            split_room: room.split_room,
+            stag: room.stag,
+            transitions: (!missing.transitions || !room.transitions.is_empty())
+                .then(|| room.transitions.into_iter().collect()),
+            extra: room.extra,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(from = "RawBench", into = "RawBench")]
+pub struct Bench {
+    pub x: f32,
+    pub y: f32,
+    /// `x`/`y` as they're written in the map data, see [`super::MapData::to_json`].
+    pub exact_position: (Number, Number),
+    /// Fields this doesn't know about, kept so they're written back.
+    pub extra: Map<String, Value>,
+}
+
+/// Bench as it is stored in the map data.
+#[derive(Serialize, Deserialize)]
+struct RawBench {
+    x: Number,
+    y: Number,
+    #[serde(flatten)]
+    extra: Map<String, Value>,
+}
+
+impl From<RawBench> for Bench {
+    fn from(raw: RawBench) -> Self {
+        let ((x, y), exact_position) = super::read_position(raw.x, raw.y);
+        Self { x, y, exact_position, extra: raw.extra }
+    }
+}
+
+impl From<Bench> for RawBench {
+    fn from(bench: Bench) -> Self {
+        let (x, y) = super::write_position((bench.x, bench.y), Some(&bench.exact_position));
+        Self { x, y, extra: bench.extra }
+    }
 }
 
 impl Room {
+    /// [`Room::calc_bounds`], only calculated the first time. The benches, items and
+    /// transitions aren't expected to change after the map data is loaded.
+    #[must_use]
+    pub fn bounds(&self) -> Rect {
+        *self.bounds_cache.get_or_init(|| self.calc_bounds())
+    }
+
+    /// Where [`Room::bounds`] ends up when the room's origin is at `pos`, like the
+    /// positions in [`crate::layout::Positions`].
+    #[must_use]
+    pub fn bounds_at(&self, (x, y): (f32, f32)) -> Rect {
+        let mut bounds = self.bounds();
+        bounds.translate([x, y + bounds.h]);
+        bounds
+    }
+
+    /// Where `point` (in map units, like [`Room::bounds_at`]) is relative to the room when its
+    /// origin is at `pos`, in the same coordinates as its benches, items and transitions.
+    #[must_use]
+    pub fn local_point(&self, (x, y): (f32, f32), point: [f32; 2]) -> (f32, f32) {
+        (point[0] - x, y + self.bounds().h - point[1])
+    }
+
+    /// The transition closest to `point` (see [`Room::local_point`]), if it's at most `radius`
+    /// away. Transitions without a position are never hit.
+    #[must_use]
+    pub fn transition_at(&self, point: (f32, f32), radius: f32) -> Option<&str> {
+        closest_within(
+            point,
+            radius,
+            self.transitions
+                .iter()
+                .filter(|(_, tr)| tr.has_position)
+                .map(|(name, tr)| (name.as_str(), (tr.x, tr.y))),
+        )
+    }
+
+    /// The item closest to `point` (see [`Room::local_point`]) that's `visible`, if it's at
+    /// most `radius` away. Items without a position are never hit.
+    pub fn item_at(
+        &self,
+        point: (f32, f32),
+        radius: f32,
+        visible: impl Fn(&Item) -> bool,
+    ) -> Option<&str> {
+        closest_within(
+            point,
+            radius,
+            self.items
+                .iter()
+                .filter(|(_, item)| item.has_position && visible(item))
+                .map(|(name, item)| (name.as_str(), (item.x, item.y))),
+        )
+    }
+
+    /// The transitions of each part of the room that's connected inside. Rooms without a
+    /// `splitRoom` are one part, transitions missing from it are treated as parts of their own.
+    ///
+    /// There's always at least one part, and they're sorted so they're always in the same order.
+    #[must_use]
+    pub fn transition_groups(&self) -> Vec<Vec<&str>> {
+        let mut keys: Vec<&str> = self.transitions.keys().map(String::as_str).collect();
+        keys.sort_unstable();
+
+        let Some(split_room) = &self.split_room else {
+            return vec![keys];
+        };
+
+        let mut grouped = HashSet::new();
+        let mut groups: Vec<Vec<&str>> = split_room
+            .iter()
+            .map(|group| {
+                let mut group: Vec<&str> = group
+                    .iter()
+                    .map(String::as_str)
+                    .filter(|k| self.transitions.contains_key(*k) && grouped.insert(*k))
+                    .collect();
+                group.sort_unstable();
+                group
+            })
+            .filter(|group| !group.is_empty())
+            .collect();
+        groups.extend(
+            keys.into_iter()
+                .filter(|k| !grouped.contains(k))
+                .map(|k| vec![k]),
+        );
+        if groups.is_empty() {
+            groups.push(Vec::new());
+        }
+        groups
+    }
+
     #[must_use]
     pub fn calc_bounds(&self) -> Rect {
         let mut min_x: f32 = 10000.0;
@@ -31,11 +246,11 @@ impl Room {
         let mut min_y: f32 = 10000.0;
         let mut max_y: f32 = -10000.0;
 
-        for (x, y) in &self.benches {
-            min_x = min_x.min(*x - 5.0);
-            max_x = max_x.max(*x + 5.0);
-            min_y = min_y.min(-*y - 5.0);
-            max_y = max_y.max(-*y + 5.0);
+        for b in &self.benches {
+            min_x = min_x.min(b.x - 5.0);
+            max_x = max_x.max(b.x + 5.0);
+            min_y = min_y.min(-b.y - 5.0);
+            max_y = max_y.max(-b.y + 5.0);
         }
 
         for i in self.items.values() {
@@ -74,369 +289,40 @@ impl Room {
             }
         }
     }
-
-    #[allow(clippy::too_many_lines)] // TODO
-    #[allow(clippy::too_many_arguments)] // TODO
-    pub fn draw(
-        &mut self,
-        ctx: &mut ggez::Context,
-        mut transform: TransformStack,
-        key: &String,
-        rando_data: &RandoData,
-        asset_cache: &HashMap<String, graphics::Image>,
-        hovered: Option<bool>,
-        selected: Option<bool>,
-        highlight_path: &Option<Vec<String>>,
-        settings: &Settings,
-    ) -> GameResult {
-        let bounds = self.calc_bounds();
-
-        if settings.debug_show_room_origins {
-            let rect = graphics::Mesh::new_circle(
-                ctx,
-                graphics::DrawMode::Stroke(StrokeOptions::default()),
-                [0.0, 0.0],
-                2.0,
-                1.0,
-                graphics::Color::from_rgba(255, 0, 0, 255),
-            )?;
-
-            graphics::draw(ctx, &rect, &transform)?;
-        }
-
-        transform.translate(0.0, bounds.h);
-
-        #[allow(clippy::match_same_arms)]
-        let (stroke_color, fill_color) = match self.area.as_deref() {
-            Some("Abyss") => (0xADACAD, 0x2D2D2D),          //Ancient Basin
-            Some("Cliffs") => (0x6B6B6B, 0x1B1B1B),         //Howling Cliffs
-            Some("Crossroads") => (0x9DC1DA, 0x2B353B),     //Forgotten Crossroads
-            Some("Deepnest") => (0x9AABC2, 0x262B30),       //Deepnest
-            Some("Deepnest_East") => (0xDFD1BE, 0x34312C),  //Kingdom's Edge
-            Some("FogCanyon") => (0xF3C8EB, 0x3C323A),      //Fog Canyon
-            Some("Fungus1") => (0xDCFFD0, 0x313D2E),        //Greenpath
-            Some("Fungus2") => (0xFAFFD3, 0x3B3D32),        //Fungal Wastes
-            Some("Fungus3") => (0x96B999, 0x232B23),        //Queen's Gardens
-            Some("Hive") => (0xFFF7A3, 0x3B3824),           //The Hive
-            Some("Mines") => (0xE4BEE8, 0x372F38),          //Crystal Peak
-            Some("RestingGrounds") => (0xFEC7A2, 0x382D24), //Resting Grounds
-            Some("Room") => (0xFEA2AD, 0x382425),           //Room
-            Some("Ruins1" | "Ruins2") => (0xB8C3FF, 0x292C3C), //City of Tears
-            Some("Town") => (0xA2A2A2, 0x2B2B2B),           //Dirtmouth
-            Some("Waterways") => (0x98FFFF, 0x243D3C),      //Royal Waterways
-            Some("White_Palace") => (0xD8D8D8, 0x333333),   //White Palace
-            _ => (0xA2A2A2, 0x2B2B2B),
-        };
-
-        let mut alpha = if let Some(true) = selected {
-            ((ggez::timer::time_since_start(ctx).as_secs_f32() / 0.33)
-                .sin()
-                .abs())
-                * 0.2
-                + 0.8
-        } else if let Some(true) = hovered {
-            0.95
-        } else if let Some(false) = selected {
-            0.6
-        } else if let Some(false) = hovered {
-            0.7
-        } else {
-            0.8
-        };
-
-        let mut path_highlight_factor = 0.0;
-        if let Some(path) = highlight_path {
-            if let Some(i) = path
-                .iter()
-                .position(|path_tr| &Transition::get_transition_info(path_tr).unwrap().0 == key)
-            {
-                let thru = ((ggez::timer::time_since_start(ctx).as_secs_f32() + i as f32) / 0.25)
-                    .sin()
-                    .max(0.25);
-                path_highlight_factor = thru;
-                alpha = alpha.max(0.8);
-            } else {
-                alpha = alpha.min(0.5);
-            }
-        }
-
-        let rect = graphics::Mesh::new_rounded_rectangle(
-            ctx,
-            graphics::DrawMode::fill(),
-            bounds,
-            5.0,
-            graphics::Color::from_rgb_u32(fill_color)
-                .lerp(&graphics::Color::from_rgba(0, 0, 0, 0), 1.0 - alpha),
-        )?;
-
-        graphics::draw(ctx, &rect, &transform)?;
-
-        let rect = graphics::Mesh::new_rounded_rectangle(
-            ctx,
-            graphics::DrawMode::stroke(2.0),
-            bounds,
-            5.0,
-            graphics::Color::from_rgb_u32(stroke_color)
-                .lerp(&Color::from_rgb(255, 100, 160), path_highlight_factor)
-                .lerp(&graphics::Color::from_rgba(0, 0, 0, 0), 1.0 - alpha),
-        )?;
-
-        graphics::draw(ctx, &rect, &transform)?;
-
-        if settings.debug_show_room_origins {
-            let rect = graphics::Mesh::new_circle(
-                ctx,
-                graphics::DrawMode::Stroke(StrokeOptions::default()),
-                [0.0, 0.0],
-                2.0,
-                1.0,
-                graphics::Color::from_rgba(0, 0, 255, 255),
-            )?;
-
-            graphics::draw(ctx, &rect, &transform)?;
-        }
-
-        // room name
-        if settings.draw_room_names {
-            transform.push();
-            transform.translate(bounds.x, bounds.y + bounds.h);
-            // TODO: cache
-            graphics::Text::new(key.clone())
-                .set_font(Font::default(), PxScale::from(12.0))
-                .draw(ctx, (&transform).into())?;
-            transform.pop();
-        }
-
-        // transitions
-
-        let transition_normal_fill = graphics::Mesh::new_polygon(
-            ctx,
-            graphics::DrawMode::fill(),
-            &[[-1.0, -12.0], [1.0, -12.0], [6.0, 4.0], [-6.0, 4.0]],
-            graphics::Color::WHITE,
-        )?;
-        let transition_door_fill = graphics::Mesh::new_polygon(
-            ctx,
-            graphics::DrawMode::fill(),
-            &[
-                [-6.0, 5.0],
-                [-6.0, -8.0],
-                [-4.0, -13.0],
-                [0.0, -15.0],
-                [4.0, -13.0],
-                [6.0, -8.0],
-                [6.0, 5.0],
-            ],
-            graphics::Color::WHITE,
-        )?;
-        let transition_normal_stroke = graphics::Mesh::new_polygon(
-            ctx,
-            graphics::DrawMode::stroke(2.0),
-            &[[-1.0, -12.0], [1.0, -12.0], [6.0, 4.0], [-6.0, 4.0]],
-            graphics::Color::WHITE,
-        )?;
-        let transition_door_stroke = graphics::Mesh::new_polygon(
-            ctx,
-            graphics::DrawMode::stroke(2.0),
-            &[
-                [-6.0, 5.0],
-                [-6.0, -8.0],
-                [-4.0, -13.0],
-                [0.0, -15.0],
-                [4.0, -13.0],
-                [6.0, -8.0],
-                [6.0, 5.0],
-            ],
-            graphics::Color::WHITE,
-        )?;
-        for (n, tr) in &self.transitions {
-            transform.push();
-            transform.translate(tr.x, -tr.y);
-
-            let transition_id = format!("{key}[{n}]");
-            let revealed = rando_data.visited_transitions.contains(&transition_id);
-
-            let scale = if revealed { 0.8 } else { 1.0 };
-            transform.scale(scale, scale);
-
-            let mut color = if revealed {
-                Color::from_rgba(150, 160, 150, 127)
-            } else {
-                Color::from_rgba(255, 255, 127, 191).lerp(
-                    &Color::WHITE,
-                    ((ggez::timer::time_since_start(ctx).as_secs_f32()) / 0.5)
-                        .sin()
-                        .abs(),
-                )
-            };
-
-            if let Some(path) = highlight_path {
-                if let Some(i) = path.iter().position(|path_tr| path_tr == &transition_id) {
-                    let thru = ((ggez::timer::time_since_start(ctx).as_secs_f32() + i as f32)
-                        / 0.25)
-                        .sin()
-                        .max(0.25);
-                    color = color.lerp(&Color::from_rgb(255, 100, 160), thru);
-                }
-            }
-
-            if n.starts_with("door") || n.starts_with("room") {
-                let param: DrawParam = Into::<DrawParam>::into(&transform).color(color);
-                graphics::draw(
-                    ctx,
-                    if revealed {
-                        &transition_door_stroke
-                    } else {
-                        &transition_door_fill
-                    },
-                    param,
-                )?;
-                // graphics::draw(ctx, &rect, &transform);
-            } else {
-                if n.starts_with("left") {
-                    transform.rotate(-90.0_f32.to_radians());
-                } else if n.starts_with("right") {
-                    transform.rotate(90.0_f32.to_radians());
-                } else if n.starts_with("bot") {
-                    transform.rotate(180.0_f32.to_radians());
-                }
-                let param: DrawParam = Into::<DrawParam>::into(&transform).color(color);
-                graphics::draw(
-                    ctx,
-                    if revealed {
-                        &transition_normal_stroke
-                    } else {
-                        &transition_normal_fill
-                    },
-                    param,
-                )?;
-                // graphics::draw(ctx, &rect, &transform);
-            }
-
-            transform.pop();
-        }
-
-        // items
-
-        let item = graphics::Mesh::new_circle(
-            ctx,
-            graphics::DrawMode::fill(),
-            [0.0, 0.0],
-            4.0,
-            2.0,
-            graphics::Color::YELLOW,
-        )?;
-        for i in self.items.values() {
-            transform.push();
-            transform.translate(i.x, -i.y);
-
-            graphics::draw(ctx, &item, &transform)?;
-
-            transform.pop();
-        }
-
-        // benches
-
-        if let Some(img) = asset_cache.get("pin_bench") {
-            // let bench = graphics::Mesh::new_rectangle(
-            //     ctx,
-            //     graphics::DrawMode::fill(),
-            //     Rect::new(-10.0, -4.0 + 3.0, 20.0, 8.0),
-            //     graphics::Color::CYAN,
-            // )?;
-            for (x, y) in &self.benches {
-                transform.push();
-                transform.translate(*x, -*y);
-
-                transform.scale(0.33, 0.33);
-                transform.translate(-(img.width() as f32) / 2.0, -(img.height() as f32) / 2.0);
-
-                graphics::draw(ctx, img, &transform)?;
-
-                transform.pop();
-            }
-        } else {
-            let bench = graphics::Mesh::new_rectangle(
-                ctx,
-                graphics::DrawMode::fill(),
-                Rect::new(-10.0, -4.0 + 3.0, 20.0, 8.0),
-                graphics::Color::CYAN,
-            )?;
-            for (x, y) in &self.benches {
-                transform.push();
-                transform.translate(*x, -*y);
-
-                graphics::draw(ctx, &bench, &transform)?;
-                // graphics::draw(ctx, &rect, &transform);
-
-                transform.pop();
-            }
-        }
-
-        Ok(())
-    }
 }
 
-impl TryFrom<&JsonValue> for Room {
-    type Error = String;
-
-    fn try_from(json: &JsonValue) -> Result<Self, Self::Error> {
-        let area = json["area"].as_str().map(Into::into);
-
-        let benches = json["benches"]
-            .members()
-            .map(|v| {
-                Ok((
-                    v["x"]
-                        .as_f32()
-                        .ok_or("Bench has missing/invalid field 'x'")?,
-                    v["y"]
-                        .as_f32()
-                        .ok_or("Bench has missing/invalid field 'y'")?,
-                ))
-            })
-            .collect::<Result<_, Self::Error>>()?;
-
-        let items = json["items"]
-            .entries()
-            .map(|(k, v)| Ok((k.into(), v.try_into()?)))
-            .collect::<Result<HashMap<String, Item>, Self::Error>>()?;
-
-        let name = json["name"].as_str().map(Into::into);
-        let randomizer_area = json["randomizerArea"].as_str().map(Into::into);
+/// The name of the closest of `candidates` to `point`, if it's at most `radius` away. Ties go
+/// to the first name in sort order so the result doesn't depend on the map order.
+fn closest_within<'a>(
+    (x, y): (f32, f32),
+    radius: f32,
+    candidates: impl Iterator<Item = (&'a str, (f32, f32))>,
+) -> Option<&'a str> {
+    candidates
+        .map(|(name, (cx, cy))| (name, (cx - x).hypot(cy - y)))
+        .filter(|&(_, distance)| distance <= radius)
+        .min_by(|a, b| a.1.total_cmp(&b.1).then(a.0.cmp(b.0)))
+        .map(|(name, _)| name)
+}
 
-        let split_room = if json["splitRoom"].is_array() {
-            Some(
-                json["splitRoom"]
-                    .members()
-                    .map(|m| {
-                        m.members()
-                            .map(|tr| {
-                                tr.as_str()
-                                    .ok_or("Room has invalid splitRoom entry")
-                                    .map(Into::into)
-                            })
-                            .collect()
-                    })
-                    .collect::<Result<Vec<_>, _>>()?,
-            )
-        } else {
-            None
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rooms_can_be_made_without_the_map_data() {
+        let room = Room {
+            name: Some("Room".into()),
+            benches: vec![Bench {
+                x: 10.0,
+                y: 20.0,
+                exact_position: (10.into(), 20.into()),
+                extra: Map::new(),
+            }],
+            ..Room::default()
         };
-
-        let transitions = json["transitions"]
-            .entries()
-            .map(|(k, v)| Ok((k.into(), v.try_into()?)))
-            .collect::<Result<HashMap<String, Transition>, Self::Error>>()?;
-
-        Ok(Self {
-            area,
-            benches,
-            items,
-            name,
-            randomizer_area,
-            split_room,
-            transitions,
-        })
+        assert!(room.bounds_cache.get().is_none());
+        assert!(room.bounds().contains([10.0, -20.0]));
+        assert_eq!(room.bounds_cache.get(), Some(&room.bounds()));
     }
 }
@@ -1,9 +1,20 @@
-use json::JsonValue;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Number, Value};
 
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(from = "RawTransition", into = "RawTransition")]
 pub struct Transition {
     pub to: Option<String>,
     pub x: f32,
     pub y: f32,
+    /// `false` if the map data doesn't have coordinates for this transition, `x`/`y` are 0 then.
+    pub has_position: bool,
+    /// `x`/`y` as they're written in the map data, see [`super::MapData::to_json`].
+    pub exact_position: Option<(Number, Number)>,
+    /// Which way the transition faces, for ones that can't be told from their name (trams, elevators, ...).
+    pub side: Option<String>,
+    /// Fields this doesn't know about, kept so they're written back.
+    pub extra: Map<String, Value>,
 }
 
 impl Transition {
@@ -15,14 +26,44 @@ impl Transition {
     }
 }
 
-impl TryFrom<&JsonValue> for Transition {
-    type Error = String;
+/// Transition as it is stored in the map data, with optional coordinates.
+#[derive(Serialize, Deserialize)]
+struct RawTransition {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    side: Option<String>,
+    // `null` for transitions that don't lead anywhere, always written out
+    #[serde(default)]
+    to: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    x: Option<Number>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    y: Option<Number>,
+    #[serde(flatten)]
+    extra: Map<String, Value>,
+}
+
+impl From<RawTransition> for Transition {
+    fn from(raw: RawTransition) -> Self {
+        let position = raw.x.zip(raw.y).map(|(x, y)| super::read_position(x, y));
+        let (x, y) = position.as_ref().map_or((0.0, 0.0), |(pos, _)| *pos);
+        Self {
+            to: raw.to,
+            x,
+            y,
+            has_position: position.is_some(),
+            exact_position: position.map(|(_, exact)| exact),
+            side: raw.side,
+            extra: raw.extra,
+        }
+    }
+}
 
-    fn try_from(json: &JsonValue) -> Result<Self, Self::Error> {
-        Ok(Self {
-            to: json["to"].as_str().map(Into::into),
-            x: json["x"].as_f32().unwrap_or(0.0),
-            y: json["y"].as_f32().unwrap_or(0.0),
-        })
+impl From<Transition> for RawTransition {
+    fn from(tr: Transition) -> Self {
+        let (x, y) = tr
+            .has_position
+            .then(|| super::write_position((tr.x, tr.y), tr.exact_position.as_ref()))
+            .unzip();
+        Self { side: tr.side, to: tr.to, x, y, extra: tr.extra }
     }
 }
@@ -0,0 +1,400 @@
+use std::{collections::HashSet, fmt::Display};
+
+use super::{room::Room, transition::Transition, MapData};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Severity {
+    Warning,
+    Error,
+}
+
+/// What's wrong, see [`validate`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProblemKind {
+    MalformedTarget,
+    MissingRoom,
+    MissingTransition,
+    NotLeadingBack,
+    OneWay,
+    SplitRoomNamesMissingTransition,
+    SplitRoomDuplicate,
+    SplitRoomIncomplete,
+    UnnamedArea,
+}
+
+impl ProblemKind {
+    #[must_use]
+    pub fn severity(self) -> Severity {
+        match self {
+            ProblemKind::OneWay => Severity::Warning,
+            _ => Severity::Error,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Problem {
+    pub severity: Severity,
+    pub kind: ProblemKind,
+    /// The room the problem was found in, `None` for problems with the file as a whole.
+    pub room: Option<String>,
+    /// The transition (`right1`) or area (`Dream`) of the room the problem is about.
+    pub key: String,
+    pub message: String,
+}
+
+impl Display for Problem {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let severity = match self.severity {
+            Severity::Warning => "warning",
+            Severity::Error => "error",
+        };
+        match &self.room {
+            Some(room) => write!(f, "{severity}: {room}: {}", self.message),
+            None => write!(f, "{severity}: {}", self.message),
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct ValidationReport {
+    pub problems: Vec<Problem>,
+}
+
+impl ValidationReport {
+    fn push(&mut self, kind: ProblemKind, room: &str, key: &str, message: String) {
+        self.problems.push(Problem {
+            severity: kind.severity(),
+            kind,
+            room: Some(room.into()),
+            key: key.into(),
+            message,
+        });
+    }
+
+    #[must_use]
+    pub fn count(&self, severity: Severity) -> usize {
+        self.problems.iter().filter(|p| p.severity == severity).count()
+    }
+
+    #[must_use]
+    pub fn has_errors(&self) -> bool {
+        self.count(Severity::Error) > 0
+    }
+}
+
+impl Display for ValidationReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for problem in &self.problems {
+            writeln!(f, "{problem}")?;
+        }
+        write!(
+            f,
+            "{} error(s), {} warning(s)",
+            self.count(Severity::Error),
+            self.count(Severity::Warning)
+        )
+    }
+}
+
+/// Errors the bundled map data is known to have, by kind, room (`None` for any room) and
+/// transition or area. They're only reported as warnings.
+const KNOWN_PROBLEMS: &[(ProblemKind, Option<&str>, &str)] = &[
+    // dreams and the randomizer's rooms that aren't on the map (the start, Steel Soul deaths,
+    // items without a room) have no area name
+    (ProblemKind::UnnamedArea, None, "Dream"),
+    (ProblemKind::UnnamedArea, None, "Grimm"),
+    (ProblemKind::UnnamedArea, None, "Knight"),
+    (ProblemKind::UnnamedArea, None, "PermaDeath"),
+    (ProblemKind::UnnamedArea, Some("__orphans__"), ""),
+    // leaving the White Palace from its end puts you at the same spot as leaving from the hub
+    (ProblemKind::NotLeadingBack, Some("White_Palace_11"), "magic_circle"),
+    // where you wake up after Soul Tyrant, the map data doesn't say which part of the room
+    // that's in
+    (ProblemKind::SplitRoomIncomplete, Some("Ruins1_24"), "door_dreamReturn"),
+];
+
+fn is_known(problem: &Problem) -> bool {
+    KNOWN_PROBLEMS.iter().any(|&(kind, room, key)| {
+        problem.kind == kind
+            && room.is_none_or(|room| problem.room.as_deref() == Some(room))
+            && problem.key == key
+    })
+}
+
+/// Checks the map data for consistency:
+/// - every `Transition::to` refers to an existing room and transition
+/// - the target transition leads back (one-way transitions are only warned about)
+/// - `split_room` groups only name existing transitions and cover all of them
+/// - `areas` has a name for every `Room::area`
+///
+/// Errors in [`KNOWN_PROBLEMS`] are reported as warnings.
+#[must_use]
+pub fn validate(map_data: &MapData) -> ValidationReport {
+    let mut report = ValidationReport::default();
+
+    for (key, room) in &map_data.rooms {
+        for (tr_key, tr) in &room.transitions {
+            let Some(to) = &tr.to else {
+                continue;
+            };
+
+            let Some((to_room_key, to_tr_key)) = Transition::get_transition_info(to) else {
+                report.push(
+                    ProblemKind::MalformedTarget,
+                    key,
+                    tr_key,
+                    format!("[{tr_key}] has malformed target '{to}'"),
+                );
+                continue;
+            };
+
+            let Some(to_room) = map_data.rooms.get(&to_room_key) else {
+                report.push(
+                    ProblemKind::MissingRoom,
+                    key,
+                    tr_key,
+                    format!("[{tr_key}] leads to missing room '{to_room_key}'"),
+                );
+                continue;
+            };
+
+            let Some(to_tr) = to_room.transitions.get(&to_tr_key) else {
+                report.push(
+                    ProblemKind::MissingTransition,
+                    key,
+                    tr_key,
+                    format!("[{tr_key}] leads to missing transition '{to}'"),
+                );
+                continue;
+            };
+
+            let this = format!("{key}[{tr_key}]");
+            match &to_tr.to {
+                Some(back) if back == &this => {},
+                Some(back) => report.push(
+                    ProblemKind::NotLeadingBack,
+                    key,
+                    tr_key,
+                    format!("[{tr_key}] leads to '{to}', which leads to '{back}' instead of back"),
+                ),
+                None => report.push(
+                    ProblemKind::OneWay,
+                    key,
+                    tr_key,
+                    format!("[{tr_key}] is one-way, '{to}' doesn't lead anywhere"),
+                ),
+            }
+        }
+
+        if let Some(groups) = &room.split_room {
+            check_split_room(&mut report, key, room, groups);
+        }
+
+        if let Some(area) = &room.area {
+            if !map_data.areas.contains_key(area) {
+                report.push(ProblemKind::UnnamedArea, key, area, format!("area '{area}' isn't in areas"));
+            }
+        }
+    }
+
+    for problem in &mut report.problems {
+        if problem.severity == Severity::Error && is_known(problem) {
+            problem.severity = Severity::Warning;
+            problem.message.push_str(" (known)");
+        }
+    }
+
+    report
+        .problems
+        .sort_by(|a, b| (&a.room, &a.message).cmp(&(&b.room, &b.message)));
+    report
+}
+
+/// `splitRoom` groups only name existing transitions and cover each of them once.
+fn check_split_room(report: &mut ValidationReport, key: &str, room: &Room, groups: &[Vec<String>]) {
+    let mut seen = HashSet::new();
+    for tr_key in groups.iter().flatten() {
+        if !room.transitions.contains_key(tr_key) {
+            report.push(
+                ProblemKind::SplitRoomNamesMissingTransition,
+                key,
+                tr_key,
+                format!("splitRoom names missing transition '{tr_key}'"),
+            );
+        }
+        if !seen.insert(tr_key) {
+            report.push(
+                ProblemKind::SplitRoomDuplicate,
+                key,
+                tr_key,
+                format!("splitRoom has '{tr_key}' in more than one group"),
+            );
+        }
+    }
+
+    let mut missing: Vec<_> = room
+        .transitions
+        .keys()
+        .filter(|tr_key| !seen.contains(tr_key))
+        .collect();
+    missing.sort();
+    for tr_key in missing {
+        report.push(
+            ProblemKind::SplitRoomIncomplete,
+            key,
+            tr_key,
+            format!("splitRoom doesn't cover transition '{tr_key}'"),
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::{load_mapdata, EMBEDDED_MAPDATA};
+
+    fn problems(rooms: &str) -> Vec<String> {
+        let json = format!(r#"{{ "areas": {{ "Town": "Dirtmouth" }}, "rooms": {rooms} }}"#);
+        let map_data = load_mapdata(&json).unwrap();
+        validate(&map_data)
+            .problems
+            .iter()
+            .map(ToString::to_string)
+            .collect()
+    }
+
+    #[test]
+    fn bundled_data_has_no_errors() {
+        let report = validate(&load_mapdata(EMBEDDED_MAPDATA).unwrap());
+        assert!(!report.has_errors(), "{report}");
+    }
+
+    #[test]
+    fn consistent_data() {
+        let problems = problems(
+            r#"{
+                "A": { "area": "Town", "transitions": {
+                    "right1": { "to": "B[left1]" },
+                    "right2": { "to": null }
+                }, "splitRoom": [["right1"], ["right2"]] },
+                "B": { "transitions": { "left1": { "to": "A[right1]" } } }
+            }"#,
+        );
+        assert_eq!(problems, Vec::<String>::new());
+    }
+
+    #[test]
+    fn broken_targets() {
+        let problems = problems(
+            r#"{
+                "A": { "transitions": {
+                    "right1": { "to": "B" },
+                    "right2": { "to": "C[left1]" },
+                    "right3": { "to": "B[left9]" }
+                } },
+                "B": { "transitions": {} }
+            }"#,
+        );
+        assert_eq!(
+            problems,
+            [
+                "error: A: [right1] has malformed target 'B'",
+                "error: A: [right2] leads to missing room 'C'",
+                "error: A: [right3] leads to missing transition 'B[left9]'",
+            ]
+        );
+    }
+
+    #[test]
+    fn transitions_that_dont_lead_back() {
+        let problems = problems(
+            r#"{
+                "A": { "transitions": {
+                    "right1": { "to": "B[left1]" },
+                    "right2": { "to": "B[left2]" }
+                } },
+                "B": { "transitions": {
+                    "left1": { "to": "A[right2]" },
+                    "left2": { "to": null }
+                } }
+            }"#,
+        );
+        assert_eq!(
+            problems,
+            [
+                "error: A: [right1] leads to 'B[left1]', which leads to 'A[right2]' instead of back",
+                "warning: A: [right2] is one-way, 'B[left2]' doesn't lead anywhere",
+                "error: B: [left1] leads to 'A[right2]', which leads to 'B[left2]' instead of back",
+            ]
+        );
+    }
+
+    #[test]
+    fn split_room_groups() {
+        let problems = problems(
+            r#"{
+                "A": { "transitions": {
+                    "left1": { "to": null },
+                    "right1": { "to": null },
+                    "top1": { "to": null }
+                }, "splitRoom": [["left1", "right9"], ["left1", "right1"]] }
+            }"#,
+        );
+        assert_eq!(
+            problems,
+            [
+                "error: A: splitRoom doesn't cover transition 'top1'",
+                "error: A: splitRoom has 'left1' in more than one group",
+                "error: A: splitRoom names missing transition 'right9'",
+            ]
+        );
+    }
+
+    #[test]
+    fn unnamed_areas() {
+        let problems = problems(r#"{ "A": { "area": "Town" }, "B": { "area": "Nowhere" } }"#);
+        assert_eq!(problems, ["error: B: area 'Nowhere' isn't in areas"]);
+    }
+
+    #[test]
+    fn known_problems_are_warnings() {
+        let problems = problems(
+            r#"{ "Dream_Abyss": { "area": "Dream" }, "__orphans__": { "area": "" }, "A": { "area": "" } }"#,
+        );
+        assert_eq!(
+            problems,
+            [
+                "error: A: area '' isn't in areas",
+                "warning: Dream_Abyss: area 'Dream' isn't in areas (known)",
+                "warning: __orphans__: area '' isn't in areas (known)",
+            ]
+        );
+    }
+
+    #[test]
+    fn known_problems_only_match_their_kind() {
+        let problems = problems(
+            r#"{
+                "White_Palace_11": { "transitions": {
+                    "magic_circle": { "to": "Abyss_05[dream]" },
+                    "door1": { "to": "Abyss_05[door1]" }
+                } },
+                "Abyss_05": { "transitions": {
+                    "dream": { "to": "White_Palace_03_hub[magic_circle]" }
+                } },
+                "White_Palace_03_hub": { "transitions": {
+                    "magic_circle": { "to": "Abyss_05[dream]" }
+                } }
+            }"#,
+        );
+        // a broken magic_circle would still be an error
+        assert_eq!(
+            problems,
+            [
+                "error: White_Palace_11: [door1] leads to missing transition 'Abyss_05[door1]'",
+                "warning: White_Palace_11: [magic_circle] leads to 'Abyss_05[dream]', which leads to 'White_Palace_03_hub[magic_circle]' instead of back (known)",
+            ]
+        );
+    }
+}
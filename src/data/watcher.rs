@@ -0,0 +1,63 @@
+use std::{
+    path::PathBuf,
+    time::{Duration, Instant, SystemTime},
+};
+
+use super::MapData;
+
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Polls a map data file for changes.
+pub struct MapDataWatcher {
+    pub path: PathBuf,
+    /// When the file was modified as of the last successful load.
+    last_modified: Option<SystemTime>,
+    /// Why the last load failed, a file that fails is tried again on every poll since the
+    /// write that fixes it can have the same modification time.
+    last_error: Option<String>,
+    last_poll: Instant,
+}
+
+impl MapDataWatcher {
+    #[must_use]
+    pub fn new(path: PathBuf) -> Self {
+        let last_modified = Self::modified(&path);
+        Self {
+            path,
+            last_modified,
+            last_error: None,
+            last_poll: Instant::now(),
+        }
+    }
+
+    fn modified(path: &PathBuf) -> Option<SystemTime> {
+        std::fs::metadata(path).and_then(|m| m.modified()).ok()
+    }
+
+    /// Returns the reloaded map data if the file changed since it was last loaded. The same
+    /// error isn't returned twice in a row.
+    pub fn poll(&mut self) -> Option<Result<MapData, String>> {
+        if self.last_poll.elapsed() < POLL_INTERVAL {
+            return None;
+        }
+        self.last_poll = Instant::now();
+
+        let modified = Self::modified(&self.path);
+        if modified.is_none() || (modified == self.last_modified && self.last_error.is_none()) {
+            return None;
+        }
+
+        match super::read_mapdata(&self.path) {
+            Ok(map_data) => {
+                self.last_modified = modified;
+                self.last_error = None;
+                Some(Ok(map_data))
+            },
+            Err(e) if self.last_error.as_ref() == Some(&e) => None,
+            Err(e) => {
+                self.last_error = Some(e.clone());
+                Some(Err(e))
+            },
+        }
+    }
+}
@@ -0,0 +1,882 @@
+use std::{
+    cmp::Ordering,
+    collections::{BTreeSet, BinaryHeap, HashMap, VecDeque},
+};
+
+use crate::data::{item::Item, room::Room, transition::Transition, MapData, RandoData};
+
+/// What going through a transition costs on top of walking to it, in map units.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct PathCosts {
+    pub transition: f32,
+    /// Doors, which take a bit to go through.
+    pub door: f32,
+    /// Elevators, trams and stags, which take a while to ride.
+    pub elevator: f32,
+}
+
+impl Default for PathCosts {
+    fn default() -> Self {
+        Self { transition: 50.0, door: 150.0, elevator: 500.0 }
+    }
+}
+
+impl PathCosts {
+    /// The cost of the transition `transition` (the part inside the brackets).
+    #[must_use]
+    pub fn of(&self, transition: &str) -> f32 {
+        if ["elev", "tram", "stag"]
+            .iter()
+            .any(|prefix| transition.starts_with(prefix))
+        {
+            self.elevator
+        } else if transition.starts_with("door") || transition.starts_with("room") {
+            self.door
+        } else {
+            self.transition
+        }
+    }
+}
+
+/// The transitions of the rooms and the ways between them as an indexed adjacency list.
+///
+/// Every transition is a node, connected to the other transitions of its room it can be
+/// walked to and to the transition it leads to if it's been visited. Rooms that are split
+/// into parts which aren't connected inside (`splitRoom` in the map data) only connect the
+/// transitions of each part, so paths can't go through a room between two transitions that
+/// don't connect.
+///
+/// Building it formats and looks up every transition name once, so it should be kept around
+/// and only built again when the visited transitions, the map data or the costs change.
+#[derive(Default)]
+pub struct Graph {
+    /// Sorted by room and transition, so node indices (and everything iterating them) are
+    /// deterministic.
+    nodes: Vec<Node>,
+    room_nodes: HashMap<String, Vec<usize>>,
+    edges: Vec<Vec<Edge>>,
+    costs: PathCosts,
+}
+
+/// A transition of a room.
+#[derive(Clone, Debug)]
+pub struct Node {
+    pub room: String,
+    pub transition: String,
+    pub visited: bool,
+}
+
+#[derive(Clone, Debug)]
+pub struct Edge {
+    pub to: usize,
+    /// The transition taken, as `Room[transition]`, or `None` for walking to another
+    /// transition of the same room.
+    pub transition: Option<String>,
+    pub cost: f32,
+}
+
+/// What [`Graph::nearest`] can look for.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Destination {
+    Bench,
+    /// Stag stations, the locations with the `Stag` pool.
+    Stag,
+    /// Transitions that haven't been visited yet.
+    UnvisitedTransition,
+}
+
+/// Where [`Graph::nearest`] found what it was looking for.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Route {
+    pub room: String,
+    /// The transitions taken to get there, ending with the transition itself when looking
+    /// for an [`Destination::UnvisitedTransition`].
+    pub path: Vec<String>,
+}
+
+impl Graph {
+    #[must_use]
+    pub fn new(map_data: &MapData, rando_data: &RandoData, costs: PathCosts) -> Self {
+        let mut rooms: Vec<_> = map_data.rooms.iter().collect();
+        rooms.sort_by_key(|(key, _)| *key);
+
+        let mut nodes = Vec::new();
+        let mut room_nodes: HashMap<String, Vec<usize>> = HashMap::new();
+        // `Room[transition]` to its node
+        let mut transition_nodes: HashMap<String, usize> = HashMap::new();
+        let mut edges: Vec<Vec<Edge>> = Vec::new();
+        for (key, room) in rooms {
+            let mut transitions: Vec<&String> = room.transitions.keys().collect();
+            transitions.sort();
+            let first = nodes.len();
+            for k in &transitions {
+                transition_nodes.insert(format!("{key}[{k}]"), nodes.len());
+                let visited = rando_data
+                    .visited_transitions
+                    .contains(&format!("{key}[{k}]"));
+                nodes.push(Node {
+                    room: key.clone(),
+                    transition: (*k).clone(),
+                    visited,
+                });
+                edges.push(Vec::new());
+            }
+            room_nodes.insert(key.clone(), (first..nodes.len()).collect());
+            let node_of =
+                |k: &str| first + transitions.binary_search_by(|t| t.as_str().cmp(k)).unwrap();
+
+            // walking between the transitions of each part
+            for group in room.transition_groups() {
+                for from in &group {
+                    let from_node = node_of(from);
+                    for to in group.iter().filter(|to| *to != from) {
+                        edges[from_node].push(Edge {
+                            to: node_of(to),
+                            transition: None,
+                            cost: walking_distance(room, from, to),
+                        });
+                    }
+                }
+            }
+        }
+
+        for (node, Node { room, transition: k, .. }) in nodes.iter().enumerate() {
+            let transition = format!("{room}[{k}]");
+            let Some((to_room, to_transition)) = visited_target(rando_data, &transition) else {
+                continue;
+            };
+            let to = match transition_nodes.get(&format!("{to_room}[{to_transition}]")) {
+                Some(&to) => to,
+                // the map data doesn't know the transition, fine as long as the room has
+                // only one way in
+                None => match room_nodes.get(&to_room).map(Vec::as_slice) {
+                    Some(&[to]) => to,
+                    _ => continue,
+                },
+            };
+            edges[node].push(Edge {
+                to,
+                transition: Some(transition),
+                cost: costs.of(k).max(costs.of(&to_transition)),
+            });
+        }
+
+        Self { nodes, room_nodes, edges, costs }
+    }
+
+    /// The costs the graph was built with.
+    #[must_use]
+    pub fn costs(&self) -> PathCosts {
+        self.costs
+    }
+
+    /// The transition nodes of `room`.
+    #[must_use]
+    pub fn nodes_of(&self, room: &str) -> &[usize] {
+        self.room_nodes.get(room).map_or(&[], Vec::as_slice)
+    }
+
+    #[must_use]
+    pub fn node(&self, node: usize) -> &Node {
+        &self.nodes[node]
+    }
+
+    #[must_use]
+    pub fn edges(&self, node: usize) -> &[Edge] {
+        &self.edges[node]
+    }
+
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    /// Finds the quickest path from `src` to `dst` through visited transitions,
+    /// as the list of transitions (`Room[transition]`) taken. The path can start from any
+    /// transition of `src`. The path from a room to itself is empty, even if it has no
+    /// transitions.
+    #[must_use]
+    pub fn find_path(&self, src: &str, dst: &str) -> Option<Vec<String>> {
+        if src == dst {
+            return Some(Vec::new());
+        }
+        let path = self.a_star(self.nodes_of(src), self.nodes_of(dst), |_| 0.0)?;
+        Some(
+            path.into_iter()
+                .filter_map(|e| e.transition.clone())
+                .collect(),
+        )
+    }
+
+    /// A* from any of the `src` nodes to the closest of the `dst` nodes, returning the edges
+    /// taken. `heuristic` estimates the cost left from a node to `dst` and has to never
+    /// overestimate it for the path to be the shortest, `|_| 0.0` makes this Dijkstra's
+    /// algorithm.
+    pub fn a_star(
+        &self,
+        src: &[usize],
+        dst: &[usize],
+        heuristic: impl Fn(usize) -> f32,
+    ) -> Option<Vec<&Edge>> {
+        let mut best_cost = vec![f32::INFINITY; self.nodes.len()];
+        let mut prev: Vec<Option<(usize, &Edge)>> = vec![None; self.nodes.len()];
+        let mut heap = BinaryHeap::new();
+
+        for &node in src {
+            best_cost[node] = 0.0;
+            heap.push(Visit { estimate: heuristic(node), node });
+        }
+
+        while let Some(Visit { estimate, node }) = heap.pop() {
+            if dst.contains(&node) {
+                return Some(path_to(&prev, node));
+            }
+
+            // stale entry, the node was reached cheaper since it was pushed
+            if estimate > best_cost[node] + heuristic(node) {
+                continue;
+            }
+
+            for edge in &self.edges[node] {
+                let cost = best_cost[node] + edge.cost;
+                if cost < best_cost[edge.to] {
+                    best_cost[edge.to] = cost;
+                    prev[edge.to] = Some((node, edge));
+                    heap.push(Visit { estimate: cost + heuristic(edge.to), node: edge.to });
+                }
+            }
+        }
+
+        None
+    }
+
+    /// The quickest way from `src` to the closest `destination`.
+    #[must_use]
+    pub fn nearest(
+        &self,
+        map_data: &MapData,
+        src: &str,
+        destination: Destination,
+    ) -> Option<Route> {
+        if self.nodes_of(src).is_empty() {
+            // without transitions there's nowhere to go, only `src` itself can have it
+            let room = map_data.rooms.get(src)?;
+            let here = match destination {
+                Destination::Bench => !room.benches.is_empty(),
+                Destination::Stag => room.items.values().any(is_stag),
+                Destination::UnvisitedTransition => false,
+            };
+            return here.then(|| Route { room: src.into(), path: Vec::new() });
+        }
+
+        let (node, edges) = self.nearest_by(self.nodes_of(src), |node| {
+            let node = &self.nodes[node];
+            let room = map_data.rooms.get(&node.room)?;
+            let from = transition_position(room, &node.transition);
+            match destination {
+                Destination::Bench => closest(from, room.benches.iter().map(|b| (b.x, b.y))),
+                Destination::Stag => closest(
+                    from,
+                    room.items
+                        .values()
+                        .filter(|item| is_stag(item))
+                        .map(|item| item_position(room, item)),
+                ),
+                Destination::UnvisitedTransition => (!node.visited).then_some(0.0),
+            }
+        })?;
+
+        let node = &self.nodes[node];
+        let mut path: Vec<String> = edges
+            .into_iter()
+            .filter_map(|e| e.transition.clone())
+            .collect();
+        if destination == Destination::UnvisitedTransition {
+            path.push(format!("{}[{}]", node.room, node.transition));
+        }
+        Some(Route { room: node.room.clone(), path })
+    }
+
+    /// Dijkstra's algorithm from any of the `src` nodes until it gets to something.
+    /// `finish` is the cost from a node to the closest thing being looked for in its room,
+    /// `None` if there's nothing there. Returns the node it was reached from and the edges
+    /// taken to that node.
+    pub fn nearest_by(
+        &self,
+        src: &[usize],
+        finish: impl Fn(usize) -> Option<f32>,
+    ) -> Option<(usize, Vec<&Edge>)> {
+        // one past the real nodes, for the thing being looked for
+        let goal = self.nodes.len();
+        let mut best_cost = vec![f32::INFINITY; self.nodes.len()];
+        let mut prev: Vec<Option<(usize, &Edge)>> = vec![None; self.nodes.len()];
+        let mut goal_cost = f32::INFINITY;
+        let mut goal_from = None;
+        let mut heap = BinaryHeap::new();
+
+        for &node in src {
+            best_cost[node] = 0.0;
+            heap.push(Visit { estimate: 0.0, node });
+        }
+
+        while let Some(Visit { estimate, node }) = heap.pop() {
+            if node == goal {
+                let from = goal_from?;
+                return Some((from, path_to(&prev, from)));
+            }
+
+            // stale entry, the node was reached cheaper since it was pushed
+            if estimate > best_cost[node] {
+                continue;
+            }
+
+            if let Some(cost) = finish(node).map(|cost| best_cost[node] + cost) {
+                if cost < goal_cost {
+                    goal_cost = cost;
+                    goal_from = Some(node);
+                    heap.push(Visit { estimate: cost, node: goal });
+                }
+            }
+
+            for edge in &self.edges[node] {
+                let cost = best_cost[node] + edge.cost;
+                if cost < best_cost[edge.to] {
+                    best_cost[edge.to] = cost;
+                    prev[edge.to] = Some((node, edge));
+                    heap.push(Visit { estimate: cost, node: edge.to });
+                }
+            }
+        }
+
+        None
+    }
+
+    /// Every room reachable from any part of `start` through at most `depth` visited
+    /// transitions.
+    #[must_use]
+    pub fn rooms_within(&self, start: &str, depth: u8) -> BTreeSet<String> {
+        self.breadth_first(start, depth.into())
+    }
+
+    /// Every room reachable from any part of `start` through visited transitions.
+    #[must_use]
+    pub fn reachable_rooms(&self, start: &str) -> BTreeSet<String> {
+        self.breadth_first(start, usize::MAX)
+    }
+
+    fn breadth_first(&self, start: &str, depth: usize) -> BTreeSet<String> {
+        let mut rooms = BTreeSet::new();
+        rooms.insert(start.to_owned());
+
+        // transitions taken to get to each node, walking inside a room is free
+        let mut taken = vec![usize::MAX; self.nodes.len()];
+        let mut queue = VecDeque::new();
+        for &node in self.nodes_of(start) {
+            taken[node] = 0;
+            queue.push_back(node);
+        }
+        while let Some(node) = queue.pop_front() {
+            for edge in &self.edges[node] {
+                let d = taken[node] + usize::from(edge.transition.is_some());
+                if d <= depth && d < taken[edge.to] {
+                    taken[edge.to] = d;
+                    rooms.insert(self.nodes[edge.to].room.clone());
+                    if edge.transition.is_some() {
+                        queue.push_back(edge.to);
+                    } else {
+                        queue.push_front(edge.to);
+                    }
+                }
+            }
+        }
+        rooms
+    }
+}
+
+/// How far apart two transitions of `room` are.
+fn walking_distance(room: &Room, from: &str, to: &str) -> f32 {
+    let (from_x, from_y) = transition_position(room, from);
+    let (to_x, to_y) = transition_position(room, to);
+    (to_x - from_x).hypot(to_y - from_y)
+}
+
+/// Where a transition is in `room`, the middle of the room if the map data doesn't know.
+fn transition_position(room: &Room, transition: &str) -> (f32, f32) {
+    match room.transitions.get(transition) {
+        Some(tr) if tr.has_position => (tr.x, tr.y),
+        _ => room_center(room),
+    }
+}
+
+/// Whether `item` is a stag station.
+fn is_stag(item: &Item) -> bool {
+    item.rand_pool == "Stag"
+}
+
+/// Where an item is in `room`, the middle of the room if the map data doesn't know.
+fn item_position(room: &Room, item: &Item) -> (f32, f32) {
+    if item.has_position {
+        (item.x, item.y)
+    } else {
+        room_center(room)
+    }
+}
+
+/// Distance from `from` to the closest of `points`.
+fn closest(from: (f32, f32), points: impl Iterator<Item = (f32, f32)>) -> Option<f32> {
+    points
+        .map(|(x, y)| (x - from.0).hypot(y - from.1))
+        .min_by(f32::total_cmp)
+}
+
+fn room_center(room: &Room) -> (f32, f32) {
+    let center = room.bounds().center();
+    (center.x, -center.y)
+}
+
+/// The edges taken to `node`, following `prev` back to where the search started.
+fn path_to<'a>(prev: &[Option<(usize, &'a Edge)>], node: usize) -> Vec<&'a Edge> {
+    let mut path = Vec::new();
+    let mut at = node;
+    while let Some((from, edge)) = prev[at] {
+        path.push(edge);
+        at = from;
+    }
+    path.reverse();
+    path
+}
+
+/// Where the transition `from` (`Room[transition]`) leads if it's been visited.
+fn visited_target(rando_data: &RandoData, from: &String) -> Option<(String, String)> {
+    if rando_data.visited_transitions.contains(from) {
+        Transition::get_transition_info(rando_data.transition_map.get(from).unwrap_or(from))
+    } else {
+        None
+    }
+}
+
+/// Heap entry of [`Graph::a_star`], ordered so the [`BinaryHeap`] pops the lowest estimate
+/// first (and the lowest node on ties, so the result doesn't depend on push order).
+struct Visit {
+    estimate: f32,
+    node: usize,
+}
+
+impl PartialEq for Visit {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Visit {}
+
+impl PartialOrd for Visit {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Visit {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other
+            .estimate
+            .total_cmp(&self.estimate)
+            .then_with(|| other.node.cmp(&self.node))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::load_mapdata;
+
+    /// Map data with only `rooms` (a JSON object of rooms), and rando data with the vanilla
+    /// transitions of which `visited` were visited.
+    fn fixture(rooms: &str, visited: &[&str]) -> (MapData, RandoData) {
+        let map_data = load_mapdata(&format!(r#"{{ "areas": {{}}, "rooms": {rooms} }}"#)).unwrap();
+
+        let mut rando_data = RandoData::default();
+        rando_data.fill_vanilla_transitions(&map_data);
+        visit(&mut rando_data, visited);
+
+        (map_data, rando_data)
+    }
+
+    /// A - B - C in a line. D next to A and the shortcut from A to C are there, but their
+    /// transitions weren't visited.
+    fn line_map() -> (MapData, RandoData) {
+        fixture(
+            r#"{
+                "A": { "transitions": {
+                    "right1": { "to": "B[left1]" },
+                    "right2": { "to": "C[left2]" },
+                    "left1": { "to": "D[right1]" }
+                } },
+                "B": { "transitions": {
+                    "left1": { "to": "A[right1]" },
+                    "right1": { "to": "C[left1]" }
+                } },
+                "C": { "transitions": {
+                    "left1": { "to": "B[right1]" },
+                    "left2": { "to": "A[right2]" }
+                } },
+                "D": { "transitions": {
+                    "right1": { "to": "A[left1]" }
+                } }
+            }"#,
+            &["A[right1]", "B[left1]", "B[right1]", "C[left1]"],
+        )
+    }
+
+    fn visit(rando_data: &mut RandoData, transitions: &[&str]) {
+        for transition in transitions {
+            rando_data.visited_transitions.insert((*transition).into());
+        }
+    }
+
+    #[test]
+    fn path_through_visited_transitions() {
+        let (map_data, rando_data) = line_map();
+        let graph = Graph::new(&map_data, &rando_data, PathCosts::default());
+
+        assert_eq!(
+            graph.find_path("A", "C"),
+            Some(vec!["A[right1]".into(), "B[right1]".into()])
+        );
+        assert_eq!(
+            graph.find_path("C", "A"),
+            Some(vec!["C[left1]".into(), "B[left1]".into()])
+        );
+    }
+
+    #[test]
+    fn path_to_itself_is_empty() {
+        let (map_data, rando_data) = line_map();
+        let graph = Graph::new(&map_data, &rando_data, PathCosts::default());
+
+        assert_eq!(graph.find_path("B", "B"), Some(vec![]));
+    }
+
+    #[test]
+    fn unvisited_transitions_are_not_used() {
+        let (map_data, rando_data) = line_map();
+        let graph = Graph::new(&map_data, &rando_data, PathCosts::default());
+
+        assert_eq!(graph.find_path("A", "D"), None);
+        assert_eq!(graph.find_path("A", "Nowhere"), None);
+    }
+
+    #[test]
+    fn shorter_path_once_visited() {
+        let (map_data, mut rando_data) = line_map();
+        visit(&mut rando_data, &["A[right2]", "C[left2]"]);
+        let graph = Graph::new(&map_data, &rando_data, PathCosts::default());
+
+        assert_eq!(graph.find_path("A", "C"), Some(vec!["A[right2]".into()]));
+    }
+
+    #[test]
+    fn randomized_transitions_follow_the_transition_map() {
+        let (map_data, mut rando_data) = line_map();
+        // A[left1] leads to C instead of D
+        rando_data
+            .transition_map
+            .insert("A[left1]".into(), "C[left2]".into());
+        visit(&mut rando_data, &["A[left1]"]);
+        let graph = Graph::new(&map_data, &rando_data, PathCosts::default());
+
+        assert_eq!(graph.find_path("A", "C"), Some(vec!["A[left1]".into()]));
+        assert_eq!(graph.find_path("A", "D"), None);
+    }
+
+    #[test]
+    fn a_star_matches_dijkstra() {
+        let (map_data, mut rando_data) = line_map();
+        visit(&mut rando_data, &["A[left1]", "D[right1]"]);
+        let graph = Graph::new(&map_data, &rando_data, PathCosts::default());
+
+        let c = graph.nodes_of("C");
+        // at least one more transition if it's not in C yet, never more than the real cost
+        let hops = |node: usize| match graph.node(node).room.as_str() {
+            "C" => 0.0,
+            _ => PathCosts::default().transition,
+        };
+        for src in ["A", "B", "C", "D"] {
+            let src = graph.nodes_of(src);
+            let dijkstra = graph.a_star(src, c, |_| 0.0).map(|p| p.len());
+            let a_star = graph.a_star(src, c, hops).map(|p| p.len());
+            assert_eq!(dijkstra, a_star);
+        }
+    }
+
+    #[test]
+    fn rooms_within_depth() {
+        let (map_data, rando_data) = line_map();
+        let graph = Graph::new(&map_data, &rando_data, PathCosts::default());
+
+        let names = |rooms: BTreeSet<String>| rooms.into_iter().collect::<Vec<_>>().join(",");
+        assert_eq!(names(graph.rooms_within("A", 0)), "A");
+        assert_eq!(names(graph.rooms_within("A", 1)), "A,B");
+        assert_eq!(names(graph.rooms_within("A", 2)), "A,B,C");
+        assert_eq!(names(graph.reachable_rooms("A")), "A,B,C");
+        // unknown rooms are still part of their own neighbourhood
+        assert_eq!(names(graph.reachable_rooms("Nowhere")), "Nowhere");
+    }
+
+    /// A - S - B where S is split between its left and right side, and C which is
+    /// connected to both sides of S.
+    fn split_map() -> (MapData, RandoData) {
+        fixture(
+            r#"{
+                "A": { "transitions": { "right1": { "to": "S[left1]" } } },
+                "S": {
+                    "splitRoom": [["left1", "top1"], ["right1", "bot1"]],
+                    "transitions": {
+                        "left1": { "to": "A[right1]" },
+                        "top1": { "to": "C[bot1]" },
+                        "right1": { "to": "B[left1]" },
+                        "bot1": { "to": "C[top1]" }
+                    }
+                },
+                "B": { "transitions": { "left1": { "to": "S[right1]" } } },
+                "C": { "transitions": {
+                    "bot1": { "to": "S[top1]" },
+                    "top1": { "to": "S[bot1]" }
+                } }
+            }"#,
+            &["A[right1]", "S[left1]", "S[right1]", "B[left1]"],
+        )
+    }
+
+    #[test]
+    fn split_rooms_only_connect_inside_each_part() {
+        let (map_data, rando_data) = split_map();
+        let graph = Graph::new(&map_data, &rando_data, PathCosts::default());
+
+        let walks_to = |room: &str, transition: &str| {
+            let node = graph
+                .nodes_of(room)
+                .iter()
+                .copied()
+                .find(|&node| graph.node(node).transition == transition)
+                .unwrap();
+            let mut to: Vec<_> = graph
+                .edges(node)
+                .iter()
+                .filter(|edge| edge.transition.is_none())
+                .map(|edge| graph.node(edge.to).transition.as_str())
+                .collect();
+            to.sort_unstable();
+            to
+        };
+        assert_eq!(walks_to("S", "left1"), ["top1"]);
+        assert_eq!(walks_to("S", "bot1"), ["right1"]);
+        assert_eq!(walks_to("C", "top1"), ["bot1"]);
+    }
+
+    #[test]
+    fn no_path_between_the_parts_of_a_split_room() {
+        let (map_data, rando_data) = split_map();
+        let graph = Graph::new(&map_data, &rando_data, PathCosts::default());
+
+        assert_eq!(graph.find_path("A", "S"), Some(vec!["A[right1]".into()]));
+        assert_eq!(graph.find_path("B", "S"), Some(vec!["B[left1]".into()]));
+        assert_eq!(graph.find_path("A", "B"), None);
+
+        let names = |rooms: BTreeSet<String>| rooms.into_iter().collect::<Vec<_>>().join(",");
+        assert_eq!(names(graph.reachable_rooms("A")), "A,S");
+        // from the split room itself, both sides are reachable
+        assert_eq!(names(graph.reachable_rooms("S")), "A,B,S");
+    }
+
+    #[test]
+    fn path_around_a_split_room() {
+        let (map_data, mut rando_data) = split_map();
+        visit(
+            &mut rando_data,
+            &["S[top1]", "C[bot1]", "C[top1]", "S[bot1]"],
+        );
+        let graph = Graph::new(&map_data, &rando_data, PathCosts::default());
+
+        assert_eq!(
+            graph.find_path("A", "B"),
+            Some(vec![
+                "A[right1]".into(),
+                "S[top1]".into(),
+                "C[top1]".into(),
+                "S[right1]".into(),
+            ])
+        );
+    }
+
+    /// A big room W with its transitions far apart, and a detour around it through X and Y.
+    fn detour_map() -> (MapData, RandoData) {
+        let (map_data, _) = fixture(
+            r#"{
+                "A": { "transitions": {
+                    "right1": { "to": "W[left1]", "x": 100, "y": 0 },
+                    "door1": { "to": "X[left1]", "x": 100, "y": 10 }
+                } },
+                "W": { "transitions": {
+                    "left1": { "to": "A[right1]", "x": 0, "y": 0 },
+                    "right1": { "to": "B[left1]", "x": 5000, "y": 0 }
+                } },
+                "X": { "transitions": {
+                    "left1": { "to": "A[door1]", "x": 0, "y": 0 },
+                    "right1": { "to": "Y[left1]", "x": 100, "y": 0 }
+                } },
+                "Y": { "transitions": {
+                    "left1": { "to": "X[right1]", "x": 0, "y": 0 },
+                    "right1": { "to": "B[left2]", "x": 100, "y": 0 }
+                } },
+                "B": { "transitions": {
+                    "left1": { "to": "W[right1]", "x": 0, "y": 0 },
+                    "left2": { "to": "Y[right1]", "x": 0, "y": 10 }
+                } }
+            }"#,
+            &[],
+        );
+        let rando_data = RandoData::vanilla(&map_data);
+        (map_data, rando_data)
+    }
+
+    #[test]
+    fn shorter_walk_over_fewer_transitions() {
+        let (map_data, rando_data) = detour_map();
+        let graph = Graph::new(&map_data, &rando_data, PathCosts::default());
+
+        assert_eq!(
+            graph.find_path("A", "B"),
+            Some(vec![
+                "A[door1]".into(),
+                "X[right1]".into(),
+                "Y[right1]".into()
+            ])
+        );
+    }
+
+    #[test]
+    fn door_penalty() {
+        let (map_data, rando_data) = detour_map();
+        let costs = PathCosts { door: 10000.0, ..PathCosts::default() };
+        let graph = Graph::new(&map_data, &rando_data, costs);
+
+        assert_eq!(
+            graph.find_path("A", "B"),
+            Some(vec!["A[right1]".into(), "W[right1]".into()])
+        );
+    }
+
+    #[test]
+    #[allow(clippy::float_cmp)] // the exact values are copied
+    fn costs_by_kind_of_transition() {
+        let costs = PathCosts::default();
+
+        assert_eq!(costs.of("left1"), costs.transition);
+        assert_eq!(costs.of("door_stagExit"), costs.door);
+        assert_eq!(costs.of("room_grimm"), costs.door);
+        assert_eq!(costs.of("elevator"), costs.elevator);
+        assert_eq!(costs.of("elev_entrance"), costs.elevator);
+        assert_eq!(costs.of("tram_east"), costs.elevator);
+    }
+
+    /// A - B - C - D with a bench in B and a stag station in C, and C[right1] not visited.
+    fn nearest_map() -> (MapData, RandoData) {
+        fixture(
+            r#"{
+                "A": { "transitions": { "right1": { "to": "B[left1]" } } },
+                "B": {
+                    "benches": [{ "x": 10, "y": 0 }],
+                    "transitions": {
+                        "left1": { "to": "A[right1]" },
+                        "right1": { "to": "C[left1]" }
+                    }
+                },
+                "C": {
+                    "items": { "Stag": {
+                        "randAction": "Stag", "randPool": "Stag", "randType": null
+                    } },
+                    "transitions": {
+                        "left1": { "to": "B[right1]" },
+                        "right1": { "to": "D[left1]" }
+                    }
+                },
+                "D": { "transitions": { "left1": { "to": "C[right1]" } } },
+                "E": { "benches": [{ "x": 0, "y": 0 }] }
+            }"#,
+            &["A[right1]", "B[left1]", "B[right1]", "C[left1]"],
+        )
+    }
+
+    #[test]
+    fn nearest_destinations() {
+        let (map_data, rando_data) = nearest_map();
+        let graph = Graph::new(&map_data, &rando_data, PathCosts::default());
+        let route = |room: &str, path: &[&str]| Route {
+            room: room.into(),
+            path: path.iter().map(|tr| (*tr).into()).collect(),
+        };
+
+        assert_eq!(
+            graph.nearest(&map_data, "A", Destination::Bench),
+            Some(route("B", &["A[right1]"]))
+        );
+        assert_eq!(
+            graph.nearest(&map_data, "B", Destination::Bench),
+            Some(route("B", &[]))
+        );
+        assert_eq!(
+            graph.nearest(&map_data, "A", Destination::Stag),
+            Some(route("C", &["A[right1]", "B[right1]"]))
+        );
+        assert_eq!(
+            graph.nearest(&map_data, "A", Destination::UnvisitedTransition),
+            Some(route("C", &["A[right1]", "B[right1]", "C[right1]"]))
+        );
+    }
+
+    #[test]
+    fn nearest_of_nothing() {
+        let (map_data, rando_data) = nearest_map();
+        let graph = Graph::new(&map_data, &rando_data, PathCosts::default());
+
+        // D can't get anywhere and only has its own unvisited transition
+        assert_eq!(graph.nearest(&map_data, "D", Destination::Bench), None);
+        assert_eq!(graph.nearest(&map_data, "D", Destination::Stag), None);
+        assert_eq!(
+            graph
+                .nearest(&map_data, "D", Destination::UnvisitedTransition)
+                .map(|route| route.path),
+            Some(vec!["D[left1]".into()])
+        );
+        assert_eq!(
+            graph.nearest(&map_data, "Nowhere", Destination::Bench),
+            None
+        );
+    }
+
+    #[test]
+    fn room_without_transitions() {
+        let (map_data, rando_data) = nearest_map();
+        let graph = Graph::new(&map_data, &rando_data, PathCosts::default());
+
+        // E has a bench but no way in or out
+        assert_eq!(
+            graph.nearest(&map_data, "E", Destination::Bench),
+            Some(Route { room: "E".into(), path: Vec::new() })
+        );
+        assert_eq!(graph.nearest(&map_data, "E", Destination::Stag), None);
+        assert_eq!(
+            graph.nearest(&map_data, "E", Destination::UnvisitedTransition),
+            None
+        );
+        assert_eq!(graph.find_path("E", "E"), Some(Vec::new()));
+        assert_eq!(graph.find_path("E", "A"), None);
+        assert_eq!(graph.find_path("A", "E"), None);
+    }
+}
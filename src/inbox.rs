@@ -0,0 +1,75 @@
+use std::{
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        mpsc::{self, Receiver, Sender},
+        Arc,
+    },
+    time::Instant,
+};
+
+use json::JsonValue;
+
+/// A raw message along with when it was received.
+pub struct Received {
+    pub time: Instant,
+    pub json: JsonValue,
+}
+
+/// Unbounded queue of incoming messages that keeps track of how many are waiting.
+///
+/// It's unbounded on purpose: the UI drains the whole queue every frame, so it only grows
+/// while a frame takes long (e.g. loading a save). Bounding it would either block the socket
+/// thread, which then misses pings and drops the connection, or drop messages, and a lost
+/// `revealTransition` or `getItem` can't be recovered until the save is loaded again. The
+/// depth is shown in the messages panel in case it ever does pile up.
+#[must_use]
+pub fn inbox() -> (InboxSender, Inbox) {
+    let (send, recv) = mpsc::channel();
+    let depth = Arc::new(AtomicUsize::new(0));
+    (
+        InboxSender { send, depth: Arc::clone(&depth) },
+        Inbox { recv, depth },
+    )
+}
+
+#[derive(Clone)]
+pub struct InboxSender {
+    send: Sender<Received>,
+    depth: Arc<AtomicUsize>,
+}
+
+impl InboxSender {
+    /// Returns `false` if the [`Inbox`] was dropped.
+    #[must_use]
+    pub fn send(&self, json: JsonValue) -> bool {
+        self.depth.fetch_add(1, Ordering::SeqCst);
+        let ok = self
+            .send
+            .send(Received { time: Instant::now(), json })
+            .is_ok();
+        if !ok {
+            self.depth.fetch_sub(1, Ordering::SeqCst);
+        }
+        ok
+    }
+}
+
+pub struct Inbox {
+    recv: Receiver<Received>,
+    depth: Arc<AtomicUsize>,
+}
+
+impl Inbox {
+    /// Takes every message currently waiting in the queue.
+    #[must_use]
+    pub fn drain(&self) -> Vec<Received> {
+        let messages: Vec<_> = self.recv.try_iter().collect();
+        self.depth.fetch_sub(messages.len(), Ordering::SeqCst);
+        messages
+    }
+
+    #[must_use]
+    pub fn depth(&self) -> usize {
+        self.depth.load(Ordering::SeqCst)
+    }
+}
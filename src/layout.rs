@@ -0,0 +1,839 @@
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap},
+    sync::mpsc,
+    time::Duration,
+};
+
+use crate::{
+    data::{room::Room, transition::Transition, MapData, RandoData},
+    graph::{Graph, PathCosts},
+    state::LoadedState,
+    util::{grid::SpatialGrid, rect::Rect},
+};
+
+/// Room origins (top left of [`Room::bounds`](crate::data::room::Room::bounds)
+/// moved down by its height), keyed by room name.
+pub type Positions = HashMap<String, (f32, f32)>;
+
+/// How long [`rough_factor`] takes to settle to `0.0`.
+const SETTLE_TIME: f32 = 1.5;
+
+/// Largest move (in map units) of a step for the layout to count as converged.
+const CONVERGED: f32 = 0.01;
+
+/// How far from the current room the local layout lets rooms drift.
+const LOCAL_EXTENT: f32 = 1000.0;
+
+/// Space left between two rooms placed next to each other by [`place_new_rooms`].
+const PLACEMENT_GAP: f32 = 20.0;
+
+/// Space left between the world map and a part of it that isn't connected to the rest.
+const COMPONENT_GAP: f32 = 200.0;
+
+/// Furthest a room can move along each axis in one step.
+const MAX_MOVE: f32 = 100.0;
+
+/// How far a room moves towards the middle of its area per step, as a fraction of the
+/// distance, at an `area_pull` of `1.0`.
+const AREA_PULL: f32 = 0.005;
+
+/// How loose the layout should be after a room transition: `0.75` right after it, loosest
+/// (`1.0`) half a second in, then settling to `0.0` by [`SETTLE_TIME`].
+#[must_use]
+pub fn rough_factor(since_transition: Duration) -> f32 {
+    1.0 - (since_transition.as_secs_f32() - 0.5)
+        .powi(2)
+        .clamp(0.0, 1.0)
+}
+
+pub struct LayoutOptions {
+    /// Upper bound on the number of steps, the first half follows the same [`rough_factor`]
+    /// schedule as the animated layout and the rest lets it settle.
+    pub iterations: u32,
+    /// Seeds where the rooms start out, the same seed always gives the same layout.
+    pub seed: u64,
+    /// How many visited transitions away from the root room to lay out.
+    pub depth: u8,
+    /// How strongly rooms are pulled towards the other rooms of their area, `0.0` turns it off.
+    pub area_pull: f32,
+}
+
+impl Default for LayoutOptions {
+    fn default() -> Self {
+        Self { iterations: 600, seed: 0, depth: 2, area_pull: 0.0 }
+    }
+}
+
+/// Lays out the rooms around `root` (which stays at the origin) until they stop moving
+/// or `options.iterations` is reached.
+///
+/// Doesn't depend on timing or hash map ordering, so the same input gives the same output.
+#[must_use]
+pub fn layout(
+    map_data: &MapData,
+    rando_data: &RandoData,
+    root: &str,
+    options: &LayoutOptions,
+) -> Positions {
+    let mut positions: Positions = Graph::new(map_data, rando_data, PathCosts::default())
+        .rooms_within(root, options.depth)
+        .into_iter()
+        .map(|key| {
+            let pos = if key == root {
+                (0.0, 0.0)
+            } else {
+                scatter(options.seed, &key)
+            };
+            (key, pos)
+        })
+        .collect();
+
+    let rough_steps = (options.iterations / 2).max(1);
+    for i in 0..options.iterations {
+        let elapsed = SETTLE_TIME * i as f32 / rough_steps as f32;
+        let rough_factor = rough_factor(Duration::from_secs_f32(elapsed));
+        let moved = step(
+            map_data,
+            rando_data,
+            &mut positions,
+            |key| key == root,
+            rough_factor,
+            options.area_pull,
+            LOCAL_EXTENT,
+        );
+        if rough_factor <= 0.0 && moved < CONVERGED {
+            break;
+        }
+    }
+
+    positions
+}
+
+/// Deterministic starting position for a room, within 200 units of the origin.
+fn scatter(seed: u64, key: &str) -> (f32, f32) {
+    // FNV-1a, so the position only depends on the seed and the room name
+    let mut hash: u64 = 0xcbf29ce484222325 ^ seed;
+    for byte in key.bytes() {
+        hash ^= u64::from(byte);
+        hash = hash.wrapping_mul(0x100000001b3);
+    }
+
+    let unit = |bits: u64| (bits & 0xffff) as f32 / 65535.0 * 2.0 - 1.0;
+    (unit(hash) * 200.0, unit(hash >> 16) * 200.0)
+}
+
+/// Runs one step of the force layout on every room in `positions` except the `pinned` ones,
+/// returning the largest distance a room moved. Rooms are kept within `extent` of the origin.
+///
+/// `area_pull` moves rooms towards the middle of the rooms with the same [`Room::area`], so
+/// the areas end up as clusters.
+///
+/// Rooms are visited in name order so a step is deterministic.
+pub fn step(
+    map_data: &MapData,
+    rando_data: &RandoData,
+    positions: &mut Positions,
+    pinned: impl Fn(&str) -> bool,
+    rough_factor: f32,
+    area_pull: f32,
+    extent: f32,
+) -> f32 {
+    let mut keys: Vec<_> = positions.keys().cloned().collect();
+    keys.sort();
+    let room_bounds: Vec<_> = keys
+        .iter()
+        .map(|key| map_data.rooms.get(key).map(Room::bounds))
+        .collect();
+
+    // where the rooms are at the start of the step, rooms move at most `MAX_MOVE` before the
+    // others are checked against them
+    let mut grid = SpatialGrid::default();
+    for (i, (key, bounds)) in keys.iter().zip(&room_bounds).enumerate() {
+        if let Some(mut bounds) = *bounds {
+            let (x, y) = positions[key];
+            bounds.translate([x, y + bounds.h]);
+            grid.insert(i, bounds);
+        }
+    }
+
+    // sum of the room centers and room count per area
+    let mut area_centers: BTreeMap<&str, (f32, f32, u32)> = BTreeMap::new();
+    if area_pull > 0.0 {
+        for (key, bounds) in keys.iter().zip(&room_bounds) {
+            let (Some(area), Some(bounds)) = (
+                map_data.rooms.get(key).and_then(|r| r.area.as_deref()),
+                bounds,
+            ) else {
+                continue;
+            };
+            let (x, y) = positions[key];
+            let center = bounds.center();
+            let sum = area_centers.entry(area).or_default();
+            sum.0 += x + center.x;
+            sum.1 += y + bounds.h + center.y;
+            sum.2 += 1;
+        }
+    }
+
+    let mut max_move: f32 = 0.0;
+    for (i, (key, &bounds)) in keys.iter().zip(&room_bounds).enumerate() {
+        if pinned(key) {
+            continue;
+        }
+
+        let (Some(cur_room), Some(bounds)) = (map_data.rooms.get(key), bounds) else {
+            continue;
+        };
+
+        let (this_x, this_y) = positions[key];
+
+        let mut movement = line_up_transitions(
+            map_data,
+            rando_data,
+            positions,
+            key,
+            cur_room,
+            bounds,
+            rough_factor,
+        );
+
+        // remove intersections
+        push_apart(
+            &keys,
+            &room_bounds,
+            &grid,
+            i,
+            positions,
+            rough_factor,
+            &mut movement,
+        );
+        let (mut move_x, mut move_y) = movement;
+
+        // pull towards the rest of the area
+        if let Some(&(sum_x, sum_y, count)) = cur_room
+            .area
+            .as_deref()
+            .and_then(|area| area_centers.get(area))
+        {
+            if count > 1 {
+                let center = bounds.center();
+                let dx = sum_x / count as f32 - (this_x + center.x);
+                let dy = sum_y / count as f32 - (this_y + bounds.h + center.y);
+                move_x += dx * AREA_PULL * area_pull * (1.0 - rough_factor);
+                move_y += dy * AREA_PULL * area_pull * (1.0 - rough_factor);
+            }
+        }
+
+        let (this_x, this_y) = positions.get_mut(key).unwrap();
+        let (old_x, old_y) = (*this_x, *this_y);
+        *this_x = (*this_x + move_x.clamp(-MAX_MOVE, MAX_MOVE)).clamp(-extent, extent);
+        *this_y = (*this_y + move_y.clamp(-MAX_MOVE, MAX_MOVE)).clamp(-extent, extent);
+        max_move = max_move.max((*this_x - old_x).abs().max((*this_y - old_y).abs()));
+    }
+
+    max_move
+}
+
+/// How far `room` should move in a [`step`] to line up its visited transitions with where they
+/// lead.
+fn line_up_transitions(
+    map_data: &MapData,
+    rando_data: &RandoData,
+    positions: &Positions,
+    key: &str,
+    room: &Room,
+    bounds: Rect,
+    rough_factor: f32,
+) -> (f32, f32) {
+    let (this_x, this_y) = positions[key];
+
+    let mut move_x = 0.0;
+    let mut move_y = 0.0;
+
+    let mut transitions: Vec<_> = room.transitions.iter().collect();
+    transitions.sort_by_key(|(k, _)| *k);
+
+    // try to line up transitions
+    for (k, tr) in transitions {
+        let transition = format!("{key}[{k}]");
+        if !rando_data.visited_transitions.contains(&transition) {
+            continue;
+        }
+        let Some((to_room, to_transition_k)) = Transition::get_transition_info(
+            rando_data
+                .transition_map
+                .get(&transition)
+                .unwrap_or(&transition),
+        ) else {
+            continue;
+        };
+        if to_room == key {
+            continue;
+        }
+        let (Some(&(other_x, other_y)), Some(next_room)) =
+            (positions.get(&to_room), map_data.rooms.get(&to_room))
+        else {
+            continue;
+        };
+        let Some(to_transition) = next_room.transitions.get(&to_transition_k) else {
+            continue;
+        };
+        let next_bounds = next_room.bounds();
+
+        // move so src lines up with dst
+        let strength = 0.005 + 0.4 * rough_factor;
+        let mut strength_x;
+        let mut strength_y;
+
+        let dx = (-tr.x + to_transition.x) - this_x + other_x;
+        let dy = (-(bounds.h - tr.y) + (next_bounds.h - to_transition.y)) - this_y + other_y;
+
+        if (k.starts_with("right") && to_transition_k.starts_with("left"))
+            || (k.starts_with("left") && to_transition_k.starts_with("right"))
+        {
+            strength_x = ((dx.abs() - 200.0) / 200.0).clamp(0.0, 0.5);
+            if (k.starts_with("right") && dx < 0.0) || (k.starts_with("left") && dx > 0.0) {
+                strength_x = 2.0;
+            }
+            strength_y = 2.0;
+        } else if (k.starts_with("top") && to_transition_k.starts_with("bot"))
+            || (k.starts_with("bot") && to_transition_k.starts_with("top"))
+        {
+            strength_x = 2.0;
+            strength_y = ((dy.abs() - 200.0) / 200.0).clamp(0.0, 0.5);
+            if (k.starts_with("top") && dy > 0.0) || (k.starts_with("bot") && dy < 0.0) {
+                strength_y = 2.0;
+            }
+        } else {
+            strength_x = ((dx.abs() - 400.0) / 400.0).clamp(0.0, 0.5);
+            strength_y = ((dy.abs() - 400.0) / 400.0).clamp(0.0, 0.5);
+        }
+
+        move_x += dx * strength * strength_x;
+        move_y += dy * strength * strength_y;
+    }
+
+    (move_x, move_y)
+}
+
+/// Adds to `movement` how far room `i` of a [`step`] should move away from the rooms it
+/// overlaps with, or is close to. `grid` has where the rooms were at the start of the step.
+fn push_apart(
+    keys: &[String],
+    room_bounds: &[Option<Rect>],
+    grid: &SpatialGrid<usize>,
+    i: usize,
+    positions: &Positions,
+    rough_factor: f32,
+    movement: &mut (f32, f32),
+) {
+    let key = &keys[i];
+    let Some(bounds) = room_bounds[i] else {
+        return;
+    };
+    let (this_x, this_y) = positions[key];
+
+    let mut nearby = bounds; // copy
+    nearby.translate([this_x, this_y + bounds.h]);
+    nearby.inflate(25.0 * 2.0 + MAX_MOVE);
+    for (&other_i, _) in grid.query(&nearby) {
+        let other_key = &keys[other_i];
+        if other_key == key {
+            continue;
+        }
+        let Some(other_bounds) = room_bounds[other_i] else {
+            continue;
+        };
+        let (other_x, other_y) = positions[other_key];
+
+        let mut tr_my_bounds = bounds; // copy
+        tr_my_bounds.translate([this_x, this_y + bounds.h]);
+        tr_my_bounds.inflate(10.0);
+
+        let mut tr_other_bounds = other_bounds; // copy
+        tr_other_bounds.translate([other_x, other_y + other_bounds.h]);
+        tr_other_bounds.inflate(10.0);
+
+        if let Some(overlap_rect) = tr_my_bounds.intersection(&tr_other_bounds) {
+            movement.0 += (tr_my_bounds.center().x - overlap_rect.center().x)
+                * 0.00005
+                * overlap_rect.w
+                * overlap_rect.h
+                * (1.0 - rough_factor);
+            movement.1 += (tr_my_bounds.center().y - overlap_rect.center().y)
+                * 0.00005
+                * overlap_rect.w
+                * overlap_rect.h
+                * (1.0 - rough_factor);
+        }
+
+        // wide area
+
+        let mut tr_my_bounds = bounds; // copy
+        tr_my_bounds.translate([this_x, this_y + bounds.h]);
+        tr_my_bounds.inflate(25.0);
+
+        let mut tr_other_bounds = other_bounds; // copy
+        tr_other_bounds.translate([other_x, other_y + other_bounds.h]);
+        tr_other_bounds.inflate(25.0);
+
+        if let Some(overlap_rect) = tr_my_bounds.intersection(&tr_other_bounds) {
+            movement.0 += (tr_my_bounds.center().x - tr_other_bounds.center().x)
+                * 0.0000005
+                * overlap_rect.w
+                * overlap_rect.h
+                * (1.0 - rough_factor);
+            movement.1 += (tr_my_bounds.center().y - tr_other_bounds.center().y)
+                * 0.0000005
+                * overlap_rect.w
+                * overlap_rect.h
+                * (1.0 - rough_factor);
+        }
+    }
+}
+
+/// One frame of the animated layout in the viewer, for the rooms within `depth` of the
+/// current room. The current room stays at the origin, and pinned rooms and the room being
+/// dragged stay where they're put.
+pub fn update_room_positions(
+    map_data: &MapData,
+    state: &mut LoadedState,
+    depth: u8,
+    rough_factor: f32,
+    area_pull: f32,
+) {
+    let rando_data = &mut state.rando_data;
+
+    // rooms further away aren't drawn, so they keep their positions for when they are again
+    let rooms = state.graph.rooms_within(&state.current_room, depth);
+    let mut positions: Positions = rooms
+        .iter()
+        .filter_map(|key| {
+            let pos = *rando_data.room_positions.get(key)?;
+            Some((key.clone(), pos))
+        })
+        .collect();
+
+    positions.insert(state.current_room.clone(), (0.0, 0.0));
+    place_new_rooms(map_data, rando_data, &mut positions, &rooms);
+
+    let dragged = state
+        .selected_room
+        .as_deref()
+        .filter(|_| state.dragging_room);
+    step(
+        map_data,
+        rando_data,
+        &mut positions,
+        |key| {
+            key == state.current_room
+                || Some(key) == dragged
+                || rando_data.pinned_rooms.contains(key)
+        },
+        rough_factor,
+        area_pull,
+        LOCAL_EXTENT,
+    );
+
+    rando_data.room_positions.extend(positions);
+}
+
+/// One frame of the world map layout, which has every room reachable from the current room.
+/// Nothing is anchored to the current room, so rooms only move to make space for new ones.
+pub fn update_world_positions(map_data: &MapData, state: &mut LoadedState, area_pull: f32) {
+    let rando_data = &mut state.rando_data;
+    let rooms = state.graph.reachable_rooms(&state.current_room);
+
+    let mut positions = std::mem::take(&mut rando_data.world_positions);
+    if !rooms.iter().any(|key| positions.contains_key(key)) {
+        // nothing here is connected to the rooms laid out so far (the player warped, or it's
+        // the first room), so these rooms go next to them instead of on top of them
+        let mut component = Positions::from([(state.current_room.clone(), (0.0, 0.0))]);
+        place_new_rooms(map_data, rando_data, &mut component, &rooms);
+        if let (Some(existing), Some(new)) =
+            (extent(map_data, &positions), extent(map_data, &component))
+        {
+            let dx = existing.right() + COMPONENT_GAP - new.left();
+            let dy = existing.top() - new.top();
+            for (x, y) in component.values_mut() {
+                *x += dx;
+                *y += dy;
+            }
+        }
+        positions.extend(component);
+    }
+    place_new_rooms(map_data, rando_data, &mut positions, &rooms);
+
+    let dragged = state
+        .selected_room
+        .as_deref()
+        .filter(|_| state.dragging_room);
+    step(
+        map_data,
+        rando_data,
+        &mut positions,
+        |key| Some(key) == dragged || rando_data.world_pinned_rooms.contains(key),
+        0.0,
+        area_pull,
+        f32::INFINITY,
+    );
+
+    rando_data.world_positions = positions;
+}
+
+/// The area the rooms at `positions` cover, `None` if there aren't any.
+fn extent(map_data: &MapData, positions: &Positions) -> Option<Rect> {
+    positions
+        .iter()
+        .filter_map(|(key, &pos)| Some(map_data.rooms.get(key)?.bounds_at(pos)))
+        .reduce(|a, b| a.union(&b))
+}
+
+/// Gives every room in `rooms` that doesn't have a position yet one next to a neighbour that
+/// does, with the transition between them lined up. Rooms not connected to a placed room
+/// through `rooms` stay without a position.
+pub fn place_new_rooms(
+    map_data: &MapData,
+    rando_data: &RandoData,
+    positions: &mut Positions,
+    rooms: &BTreeSet<String>,
+) {
+    loop {
+        let mut placed = Vec::new();
+        for key in rooms {
+            let (Some(&(x, y)), Some(room)) = (positions.get(key), map_data.rooms.get(key)) else {
+                continue;
+            };
+            let bounds = room.bounds();
+
+            let mut transitions: Vec<_> = room.transitions.iter().collect();
+            transitions.sort_by_key(|(k, _)| *k);
+            for (k, tr) in transitions {
+                let transition = format!("{key}[{k}]");
+                if !rando_data.visited_transitions.contains(&transition) {
+                    continue;
+                }
+                let Some((to_room, to_transition_k)) = Transition::get_transition_info(
+                    rando_data
+                        .transition_map
+                        .get(&transition)
+                        .unwrap_or(&transition),
+                ) else {
+                    continue;
+                };
+                if !rooms.contains(&to_room)
+                    || positions.contains_key(&to_room)
+                    || placed.iter().any(|(placed_key, _)| placed_key == &to_room)
+                {
+                    continue;
+                }
+                let Some(next_room) = map_data.rooms.get(&to_room) else {
+                    continue;
+                };
+                let Some(to_transition) = next_room.transitions.get(&to_transition_k) else {
+                    continue;
+                };
+                let next_bounds = next_room.bounds();
+
+                // the opposite of the line up force in `step`, plus a gap in the direction of the transition
+                let (gap_x, gap_y) = if k.starts_with("right") {
+                    (PLACEMENT_GAP, 0.0)
+                } else if k.starts_with("left") {
+                    (-PLACEMENT_GAP, 0.0)
+                } else if k.starts_with("top") {
+                    (0.0, -PLACEMENT_GAP)
+                } else if k.starts_with("bot") {
+                    (0.0, PLACEMENT_GAP)
+                } else {
+                    (0.0, 0.0)
+                };
+                let next_x = x + tr.x - to_transition.x + gap_x;
+                let next_y = y + (bounds.h - tr.y) - (next_bounds.h - to_transition.y) + gap_y;
+                placed.push((to_room, (next_x, next_y)));
+            }
+        }
+
+        if placed.is_empty() {
+            break;
+        }
+        positions.extend(placed);
+    }
+}
+
+/// Where [`vanilla_layout`] puts the areas that aren't on the in-game map, out of the way.
+const OFF_MAP_ANCHOR: (f32, f32) = (2400.0, -800.0);
+
+/// Where the rooms of an area are gathered by [`vanilla_layout`], with `y` growing downwards,
+/// `None` for areas it doesn't know. These are rough approximations picked by hand so
+/// neighbouring areas end up near each other, not map coordinates.
+fn area_anchor(area: Option<&str>) -> Option<(f32, f32)> {
+    match area {
+        Some("Cliffs") => Some((-1600.0, -700.0)),
+        Some("Town") => Some((0.0, -800.0)),
+        Some("Crossroads") => Some((0.0, -300.0)),
+        Some("Fungus1") => Some((-1100.0, -300.0)),
+        Some("FogCanyon") => Some((-500.0, 100.0)),
+        Some("Fungus2") => Some((-150.0, 350.0)),
+        Some("Fungus3") => Some((-1300.0, 400.0)),
+        Some("Ruins1") => Some((500.0, 300.0)),
+        Some("Ruins2") => Some((900.0, 50.0)),
+        Some("Waterways") => Some((600.0, 800.0)),
+        Some("Deepnest") => Some((-700.0, 1000.0)),
+        Some("Abyss") => Some((200.0, 1300.0)),
+        Some("Deepnest_East") => Some((1700.0, 100.0)),
+        Some("Hive") => Some((1600.0, 700.0)),
+        Some("Mines") => Some((900.0, -700.0)),
+        Some("RestingGrounds") => Some((1300.0, -300.0)),
+        Some("White_Palace") => Some((1000.0, 1600.0)),
+        // dreams, the trams and stag travel, Godhome and the randomizer's rooms (the start,
+        // Steel Soul deaths, items without a room)
+        None | Some("" | "Dream" | "Grimm" | "Knight" | "PermaDeath" | "Room") => {
+            Some(OFF_MAP_ANCHOR)
+        },
+        Some(_) => None,
+    }
+}
+
+/// Lays out every room around its area's [`area_anchor`], with the rooms of an area connected
+/// the way they are in the vanilla game. The map data doesn't have map coordinates, so the
+/// anchors only roughly approximate where the areas are on the in-game map, and areas it
+/// doesn't know go with the ones that aren't on the map.
+///
+/// Deterministic like [`layout`], `options.depth` isn't used.
+#[must_use]
+pub fn vanilla_layout(map_data: &MapData, options: &LayoutOptions) -> Positions {
+    let vanilla = RandoData::vanilla(map_data);
+
+    let mut areas: BTreeMap<Option<&str>, BTreeSet<String>> = BTreeMap::new();
+    for (key, room) in &map_data.rooms {
+        areas
+            .entry(room.area.as_deref().filter(|area| !area.is_empty()))
+            .or_default()
+            .insert(key.clone());
+    }
+
+    let mut positions = Positions::new();
+    for (area, rooms) in &areas {
+        let (anchor_x, anchor_y) = area_anchor(*area).unwrap_or(OFF_MAP_ANCHOR);
+        // rooms that aren't connected inside the area start another cluster near the anchor
+        while let Some(start) = rooms.iter().find(|key| !positions.contains_key(*key)) {
+            let (x, y) = scatter(options.seed, start);
+            positions.insert(start.clone(), (anchor_x + x, anchor_y + y));
+            place_new_rooms(map_data, &vanilla, &mut positions, rooms);
+        }
+    }
+
+    for _ in 0..options.iterations {
+        let moved = step(
+            map_data,
+            &vanilla,
+            &mut positions,
+            |_| false,
+            0.0,
+            0.0,
+            f32::INFINITY,
+        );
+        if moved < CONVERGED {
+            break;
+        }
+    }
+
+    positions
+}
+
+/// [`vanilla_layout`] of the map data, laid out on a thread of its own since it takes a while
+/// for every room. It only depends on the map data, so it's done once per load of it.
+pub struct VanillaLayout {
+    receiver: mpsc::Receiver<Positions>,
+    positions: Option<Positions>,
+}
+
+impl VanillaLayout {
+    #[must_use]
+    pub fn start(map_data: &MapData) -> Self {
+        let map_data = map_data.clone();
+        let (send, receiver) = mpsc::channel();
+        std::thread::spawn(move || {
+            // the areas are far enough apart that it doesn't need to fully settle
+            let options = LayoutOptions { iterations: 150, ..LayoutOptions::default() };
+            // fails if the map data was reloaded in the meantime, nobody wants this one then
+            let _ = send.send(vanilla_layout(&map_data, &options));
+        });
+        Self { receiver, positions: None }
+    }
+
+    /// The positions, `None` while they're still being laid out.
+    pub fn positions(&mut self) -> Option<&Positions> {
+        if self.positions.is_none() {
+            self.positions = self.receiver.try_recv().ok();
+        }
+        self.positions.as_ref()
+    }
+}
+
+/// Fills `rando_data.vanilla_positions` from `vanilla_layout` if they're empty and it's done.
+pub fn fill_vanilla_positions(rando_data: &mut RandoData, vanilla_layout: &mut VanillaLayout) {
+    if rando_data.vanilla_positions.is_empty() {
+        if let Some(positions) = vanilla_layout.positions() {
+            rando_data.vanilla_positions.clone_from(positions);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        data::{load_mapdata, EMBEDDED_MAPDATA},
+        state::{Camera, CameraTarget, RoomGrid, View},
+    };
+
+    #[test]
+    fn every_bundled_area_has_an_anchor() {
+        let map_data = load_mapdata(EMBEDDED_MAPDATA).unwrap();
+        for (key, room) in &map_data.rooms {
+            assert!(
+                area_anchor(room.area.as_deref()).is_some(),
+                "{key} is in area {:?}, which has no anchor",
+                room.area
+            );
+        }
+        assert_eq!(area_anchor(Some("Nowhere")), None);
+    }
+
+    #[test]
+    #[allow(clippy::float_cmp)] // these come out exact
+    fn rough_factor_over_time() {
+        let at = |secs: f32| rough_factor(Duration::from_secs_f32(secs));
+        assert_eq!(at(0.0), 0.75);
+        assert_eq!(at(0.5), 1.0);
+        assert!(at(1.0) < at(0.5));
+        assert_eq!(at(SETTLE_TIME), 0.0);
+        assert_eq!(at(10.0), 0.0);
+    }
+
+    #[test]
+    fn layout_is_deterministic() {
+        let options = LayoutOptions { depth: 4, ..LayoutOptions::default() };
+        // separate copies so the hash maps iterate in a different order
+        let lay_out = || {
+            let map_data = load_mapdata(EMBEDDED_MAPDATA).unwrap();
+            let rando_data = RandoData::vanilla(&map_data);
+            layout(&map_data, &rando_data, "Town", &options)
+        };
+
+        let first = lay_out();
+        assert!(first.len() > 10);
+        assert_eq!(first["Town"], (0.0, 0.0));
+        assert_eq!(first, lay_out());
+    }
+
+    #[test]
+    fn vanilla_layout_is_deterministic() {
+        let options = LayoutOptions { iterations: 20, ..LayoutOptions::default() };
+        let lay_out = || vanilla_layout(&load_mapdata(EMBEDDED_MAPDATA).unwrap(), &options);
+
+        let first = lay_out();
+        assert_eq!(
+            first.len(),
+            load_mapdata(EMBEDDED_MAPDATA).unwrap().rooms.len()
+        );
+        assert_eq!(first, lay_out());
+    }
+
+    #[test]
+    fn seed_changes_the_layout() {
+        let map_data = load_mapdata(EMBEDDED_MAPDATA).unwrap();
+        let rando_data = RandoData::vanilla(&map_data);
+        let options = LayoutOptions { depth: 4, ..LayoutOptions::default() };
+
+        let first = layout(&map_data, &rando_data, "Town", &options);
+        let reseeded = layout(
+            &map_data,
+            &rando_data,
+            "Town",
+            &LayoutOptions { seed: 1, ..options },
+        );
+        assert_eq!(
+            first.keys().collect::<BTreeSet<_>>(),
+            reseeded.keys().collect()
+        );
+        assert_ne!(first, reseeded);
+    }
+
+    #[test]
+    fn warping_starts_a_new_part_of_the_world_map() {
+        // A-B and C-D are only connected by a warp
+        let map_data = load_mapdata(
+            r#"{
+                "areas": {},
+                "rooms": {
+                    "A": { "transitions": { "right1": { "to": "B[left1]", "x": 40, "y": 5 } } },
+                    "B": { "transitions": { "left1": { "to": "A[right1]", "x": 0, "y": 5 } } },
+                    "C": { "transitions": { "right1": { "to": "D[left1]", "x": 40, "y": 5 } } },
+                    "D": { "transitions": { "left1": { "to": "C[right1]", "x": 0, "y": 5 } } }
+                }
+            }"#,
+        )
+        .unwrap();
+        let rando_data = RandoData::vanilla(&map_data);
+        let mut state = LoadedState {
+            current_room: "A".into(),
+            seed: None,
+            player_x: 0.0,
+            player_y: 0.0,
+            graph: Graph::new(&map_data, &rando_data, PathCosts::default()),
+            rando_data,
+            camera: Camera::new(CameraTarget::Player),
+            hovered_room: None,
+            selected_room: None,
+            dragging_room: false,
+            view: View::World,
+            room_grid: RoomGrid::default(),
+        };
+
+        update_world_positions(&map_data, &mut state, 0.0);
+        let before = state.rando_data.world_positions.clone();
+        let mut keys: Vec<_> = before.keys().collect();
+        keys.sort();
+        assert_eq!(keys, ["A", "B"]);
+
+        state.current_room = "C".into();
+        update_world_positions(&map_data, &mut state, 0.0);
+        let positions = &state.rando_data.world_positions;
+        assert_eq!(positions.len(), 4);
+
+        let old = extent(&map_data, &before).unwrap();
+        for key in ["C", "D"] {
+            let bounds = map_data.rooms[key].bounds_at(positions[key]);
+            assert!(
+                bounds.left() >= old.right() + COMPONENT_GAP - 1.0,
+                "{key} at {bounds:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn vanilla_layout_in_the_background() {
+        let map_data = load_mapdata(EMBEDDED_MAPDATA).unwrap();
+        let mut vanilla = VanillaLayout::start(&map_data);
+        let mut rando_data = RandoData::default();
+        while rando_data.vanilla_positions.is_empty() {
+            std::thread::sleep(Duration::from_millis(10));
+            fill_vanilla_positions(&mut rando_data, &mut vanilla);
+        }
+
+        let options = LayoutOptions { iterations: 150, ..LayoutOptions::default() };
+        assert_eq!(
+            rando_data.vanilla_positions,
+            vanilla_layout(&map_data, &options)
+        );
+
+        // moved rooms aren't put back
+        rando_data
+            .vanilla_positions
+            .insert("Town".into(), (1.0, 2.0));
+        fill_vanilla_positions(&mut rando_data, &mut vanilla);
+        assert_eq!(rando_data.vanilla_positions["Town"], (1.0, 2.0));
+    }
+}
@@ -0,0 +1,26 @@
+#![deny(clippy::all)]
+#![allow(clippy::cargo)]
+#![warn(clippy::pedantic)]
+#![allow(clippy::missing_errors_doc)]
+#![allow(clippy::missing_panics_doc)]
+#![allow(clippy::unreadable_literal)]
+#![allow(clippy::cast_sign_loss)]
+#![allow(clippy::cast_precision_loss)]
+#![allow(clippy::cast_possible_truncation)]
+#![allow(clippy::cast_possible_wrap)]
+#![allow(clippy::cast_lossless)]
+#![allow(clippy::expect_fun_call)]
+#![allow(clippy::module_name_repetitions)]
+
+pub mod arrangement;
+pub mod connection;
+pub mod data;
+pub mod graph;
+pub mod inbox;
+pub mod layout;
+pub mod message;
+pub mod recording;
+pub mod save;
+pub mod search;
+pub mod state;
+pub mod util;
@@ -12,61 +12,88 @@
 #![allow(clippy::expect_fun_call)]
 #![allow(clippy::module_name_repetitions)]
 
-pub mod data;
-pub mod settings;
-pub mod state;
-pub mod util;
+mod app;
 
 use std::{
-    collections::{BTreeSet, HashMap, HashSet},
-    sync::mpsc::{self, Receiver, SyncSender},
-    thread::JoinHandle,
+    collections::HashMap,
+    path::{Path, PathBuf},
     time::Instant,
 };
 
-use data::MapData;
+use app::{
+    args::{self, Args, Command, ParseResult},
+    inbox_stats::InboxStats,
+    render::{area, room::RoomExt},
+    settings::Settings,
+    util::{color_ext::ColorExt, transform_stack::TransformStack},
+};
 use ggez::{
     conf::{WindowMode, WindowSetup},
-    event::{self, MouseButton},
-    graphics::{self, Color, DrawParam, Drawable, Rect},
+    event::{self, KeyCode, KeyMods, MouseButton},
+    graphics::{self, Color, DrawParam, Drawable},
     input::mouse::CursorIcon,
     mint::Point2,
     Context, GameError, GameResult,
 };
 use ggez_egui::EguiBackend;
-use json::JsonValue;
-use parity_ws::{Message, Sender};
-use settings::Settings;
-use util::{color_ext::ColorExt, split::GetSplit, transform_stack::TransformStack};
-
-use crate::{
-    data::{transition::Transition, RandoData},
-    state::{Camera, CameraTarget, GameState, LoadedState},
-    util::rect_ext::RectExt,
+use rusty_map_view::{
+    arrangement::Arrangement,
+    connection::{Connection, ConnectionStatus},
+    data::{self, transition::Transition, watcher::MapDataWatcher, MapData},
+    graph::{Destination, Graph},
+    inbox::{self, Inbox},
+    layout::{self, VanillaLayout},
+    message::{self, Message},
+    recording::{self, Recorder, Replay},
+    save::{self, SaveData},
+    search::{self, SearchResult},
+    state::{Camera, CameraTarget, GameState, LoadedState, RoomGrid, View},
+    util::split::GetSplit,
 };
 
+/// How many rooms the search window lists at most.
+const SEARCH_RESULTS: usize = 30;
+
+/// How far (in pixels) the mouse can move while a button is down and still count as a click.
+const CLICK_SLOP: f32 = 2.0;
+
 struct MainState {
     pos_x: f32,
     circle: graphics::Mesh,
     map_data: MapData,
-    recv: Receiver<JsonValue>,
-    shutdown: SyncSender<()>,
-    listen_thread: Option<JoinHandle<()>>,
-    shutdown_thread: Option<JoinHandle<()>>,
+    mapdata_watcher: Option<MapDataWatcher>,
+    vanilla_layout: VanillaLayout,
+    mapdata_error: Option<String>,
+    inbox: Inbox,
+    inbox_stats: InboxStats,
+    source: MessageSource,
     game_state: GameState,
     last_transition_time: Instant,
     asset_cache: HashMap<String, graphics::Image>,
     click_start_x: f32,
     click_start_y: f32,
+    /// Whether the room held with the left button has moved past [`CLICK_SLOP`], it's only
+    /// moved (and pinned) after that.
+    room_drag_started: bool,
     path_target: Option<String>,
     highlight_path: Option<Vec<String>>,
     egui_backend: EguiBackend,
     egui_ctx: Option<egui::Context>,
     settings: Settings,
+    save_error: Option<String>,
+    /// Where room arrangements are saved per seed, `None` if there's no data dir.
+    arrangement_dir: Option<PathBuf>,
+    search_query: String,
+    search_results: Vec<SearchResult>,
+}
+
+enum MessageSource {
+    Live(Connection),
+    Replay { replay: Replay, path: String },
 }
 
 impl MainState {
-    fn new(ctx: &mut Context) -> GameResult<MainState> {
+    fn new(ctx: &mut Context, args: Args) -> GameResult<MainState> {
         let circle = graphics::Mesh::new_circle(
             ctx,
             graphics::DrawMode::fill(),
@@ -77,143 +104,136 @@ impl MainState {
         )?;
 
         println!("Loading map data...");
-        let map_data = data::load_mapdata(include_str!("../res/mapdata.json"))
-            .map_err(GameError::CustomError)?;
-
-        let (send, recv) = mpsc::sync_channel(10);
-        let (send_shutdown, recv_shutdown) = mpsc::sync_channel(1);
-        let (send_out, recv_out) = mpsc::sync_channel(1);
-
-        let shutdown_thread = std::thread::spawn(move || {
-            let out: Sender = recv_out.recv().unwrap();
-            recv_shutdown.recv().unwrap();
-            out.close(parity_ws::CloseCode::Normal).unwrap();
-        });
+        let (map_data, mapdata_watcher) = if let Some(path) = &args.mapdata {
+            let map_data = data::read_mapdata(path).map_err(GameError::CustomError)?;
+            (map_data, Some(MapDataWatcher::new(path.clone())))
+        } else {
+            let map_data =
+                data::load_mapdata(data::EMBEDDED_MAPDATA).map_err(GameError::CustomError)?;
+            (map_data, None)
+        };
+        let vanilla_layout = VanillaLayout::start(&map_data);
+
+        let (source, inbox) = if let Some(path) = &args.replay {
+            let messages = recording::read_recording(path).map_err(GameError::CustomError)?;
+            println!(
+                "Replaying {} messages from {}",
+                messages.len(),
+                path.display()
+            );
 
-        let listen_thread = std::thread::spawn(move || {
-            if let Err(error) = parity_ws::connect("ws://localhost:7900/ws", |out| {
-                send_out.send(out).unwrap();
-
-                |msg: Message| {
-                    if let Ok(data) = msg.into_text() {
-                        println!("recv {} bytes", data.len());
-                        match json::parse(&data) {
-                            Ok(json) => {
-                                send.send(json).unwrap();
-                            },
-                            Err(err) => eprintln!("json::parse: {err}"),
-                        }
-                    }
+            let (send, inbox) = inbox::inbox();
+            let replay = Replay::start(messages, args.replay_speed, send);
+            (
+                MessageSource::Replay { replay, path: path.display().to_string() },
+                inbox,
+            )
+        } else {
+            let recorder = args
+                .record
+                .as_ref()
+                .map(|path| {
+                    println!("Recording messages to {}", path.display());
+                    Recorder::create(path)
+                        .map_err(|e| GameError::CustomError(format!("{}: {e}", path.display())))
+                })
+                .transpose()?;
 
-                    Ok(())
-                }
-            }) {
-                println!("Failed to create WebSocket: {:?}", error);
-            }
-        });
+            let (connection, inbox) = Connection::start(args.url.clone(), recorder);
+            (MessageSource::Live(connection), inbox)
+        };
 
         let egui_backend = EguiBackend::default();
-        Ok(MainState {
+        let mut state = MainState {
             pos_x: 0.0,
             circle,
-            recv,
-            shutdown: send_shutdown,
-            listen_thread: Some(listen_thread),
-            shutdown_thread: Some(shutdown_thread),
+            inbox,
+            inbox_stats: InboxStats::default(),
+            source,
             game_state: GameState::Unloaded,
             map_data,
+            mapdata_watcher,
+            vanilla_layout,
+            mapdata_error: None,
             last_transition_time: Instant::now(),
             asset_cache: HashMap::new(),
             click_start_x: 0.0,
             click_start_y: 0.0,
+            room_drag_started: false,
             path_target: None,
             highlight_path: None,
-            settings: Settings::default(),
+            settings: Settings {
+                ws_url: args.url,
+                save_path: args
+                    .save
+                    .as_ref()
+                    .map(|path| path.display().to_string())
+                    .unwrap_or_default(),
+                ..Settings::default()
+            },
             egui_ctx: None,
             egui_backend,
-        })
+            save_error: None,
+            arrangement_dir: Arrangement::default_dir(),
+            search_query: String::new(),
+            search_results: Vec::new(),
+        };
+
+        if let Some(path) = &args.save {
+            state.load_save_file(path).map_err(GameError::CustomError)?;
+        }
+
+        Ok(state)
     }
 
-    fn on_message(&mut self, json: &JsonValue, ctx: &mut Context) -> GameResult {
-        // println!("{}", json["type"]);
-        match json["type"].as_str() {
-            Some("loadSave") => {
-                self.load_save(&json["data"]).expect("Failed to load data");
+    fn on_message(&mut self, msg: Message, ctx: &mut Context) -> GameResult {
+        match msg {
+            Message::LoadSave { data } => match save::load_save(&data, &self.map_data) {
+                Ok(save) => self.load_save(save),
+                Err(e) => eprintln!("Failed to load save: {e}"),
             },
-            Some("unloadSave") => {
+            Message::UnloadSave => {
+                self.save_arrangement();
                 self.game_state = GameState::Unloaded;
             },
-            Some("playerMove") => {
+            Message::PlayerMove { new_room, x, y } => {
                 if let GameState::Loaded(state) = &mut self.game_state {
-                    state.current_room = json["newRoom"]
-                        .as_str()
-                        .expect(
-                            format!("Missing/Invalid field 'newRoom': {}", json["newRoom"])
-                                .as_str(),
-                        )
-                        .into();
+                    state.current_room = new_room;
                     println!("Changed room: {}", state.current_room);
-                    state.player_x = json["x"]
-                        .as_f32()
-                        .expect(format!("Missing/Invalid field 'x': {}", json["x"]).as_str());
-                    state.player_y = json["y"]
-                        .as_f32()
-                        .expect(format!("Missing/Invalid field 'y': {}", json["y"]).as_str());
-                    state.rando_data.room_positions.clear();
+                    state.player_x = x;
+                    state.player_y = y;
+                    // keep the arrangement, just move it so the new room is at the origin
+                    let (dx, dy) = state.rando_data.recenter(&state.current_room);
+                    state.camera.shift(dx, dy);
                     self.last_transition_time = Instant::now();
                 }
             },
-            Some("playerPos") => {
+            Message::PlayerPos { x, y } => {
                 if let GameState::Loaded(state) = &mut self.game_state {
-                    state.player_x = json["x"]
-                        .as_f32()
-                        .expect(format!("Missing/Invalid field 'x': {}", json["x"]).as_str());
-                    state.player_y = json["y"]
-                        .as_f32()
-                        .expect(format!("Missing/Invalid field 'y': {}", json["y"]).as_str());
+                    state.player_x = x;
+                    state.player_y = y;
                 }
             },
-            Some("revealTransition") => {
-                let to: String = json["to"]
-                    .as_str()
-                    .expect(format!("Missing/Invalid field 'to': {}", json["to"]).as_str())
-                    .into();
+            Message::RevealTransition { to } => {
                 if let GameState::Loaded(state) = &mut self.game_state {
                     state.rando_data.visited_transitions.insert(to.clone());
                     if let Some(from) = state.rando_data.transition_map.get(&to) {
                         state.rando_data.visited_transitions.insert(from.clone());
-                        println!("Reveal transition: {}", from);
+                        println!("Reveal transition: {from}");
                     }
+                    state.rebuild_graph(&self.map_data);
                 }
-                println!("Reveal transition: {}", to);
+                println!("Reveal transition: {to}");
             },
-            Some("getItem") => {
-                let item: String = json["item"]
-                    .as_str()
-                    .expect(format!("Missing/Invalid field 'item': {}", json["item"]).as_str())
-                    .into();
-                let location: String = json["location"]
-                    .as_str()
-                    .expect(
-                        format!("Missing/Invalid field 'location': {}", json["location"]).as_str(),
-                    )
-                    .into();
-                println!("Got item: {} @ {}", item, location);
+            Message::GetItem { item, location } => {
+                println!("Got item: {item} @ {location}");
+                if let GameState::Loaded(state) = &mut self.game_state {
+                    state.rando_data.checked_locations.insert(location);
+                }
             },
-            Some("asset") => {
-                let name: String = json["name"]
-                    .as_str()
-                    .expect(format!("Missing/Invalid field 'name': {}", json["name"]).as_str())
-                    .into();
-                let data: String = json["data"]
-                    .as_str()
-                    .expect(format!("Missing/Invalid field 'data': {}", json["data"]).as_str())
-                    .into();
-                match base64::decode(data) {
-                    Ok(data) => {
-                        let decoded = image::load_from_memory(&data).map_err(|_| {
-                            GameError::ResourceLoadError("image::load_from_memory failed".into())
-                        })?;
+            Message::Asset { name, data } => match base64::decode(data) {
+                Ok(data) => match image::load_from_memory(&data) {
+                    Ok(decoded) => {
                         let rgba8 = decoded.to_rgba8();
                         let (width, height) = (rgba8.width(), rgba8.height());
 
@@ -226,421 +246,426 @@ impl MainState {
 
                         self.asset_cache.insert(name, img);
                     },
-                    Err(e) => {
-                        println!("{e:?}");
-                    },
-                }
+                    Err(e) => eprintln!("asset {name}: image::load_from_memory: {e}"),
+                },
+                Err(e) => eprintln!("asset {name}: base64::decode: {e}"),
             },
-            Some(s) => println!("Unimplemented message type: {s}"),
-            _ => println!("Message missing type!"),
         }
 
         Ok(())
     }
 
-    fn load_save(&mut self, data: &JsonValue) -> Result<(), String> {
-        let hk_ver: String = data["playerData"]["version"]
-            .as_str()
-            .expect(
-                format!(
-                    "Missing/Invalid field 'playerData.version': {}",
-                    data["version"]
-                )
-                .as_str(),
-            )
-            .into();
-        println!("hk_ver = {hk_ver}");
-
-        let rando_data = data["PolymorphicModData"]["RandomizerMod"]
-            .as_str()
-            .ok_or_else(|| "Missing data.PolymorphicModData.RandomizerMod".into())
-            .and_then(|raw_json| json::parse(raw_json).map_err(|json_err| json_err.to_string()))?;
-        let rando_ctx = data["PolymorphicModData"]["context"]
-            .as_str()
-            .ok_or_else(|| "Missing data.PolymorphicModData.context".into())
-            .and_then(|raw_json| json::parse(raw_json).map_err(|json_err| json_err.to_string()))?;
-
-        let mut transition_map = HashMap::new();
-
-        // fill with vanilla to start
-        for (id, room) in &self.map_data.rooms {
-            for (tr_id, tr) in &room.transitions {
-                if let Some(to) = &tr.to {
-                    transition_map.insert(format!("{id}[{tr_id}]"), to.clone());
-                }
+    fn reload_mapdata(&mut self) {
+        if let Some(watcher) = &mut self.mapdata_watcher {
+            match watcher.poll() {
+                Some(Ok(map_data)) => {
+                    println!("Reloaded map data from {}", watcher.path.display());
+                    self.map_data = map_data;
+                    self.vanilla_layout = VanillaLayout::start(&self.map_data);
+                    self.search_results =
+                        search::search_rooms(&self.map_data, &self.search_query, SEARCH_RESULTS);
+                    self.mapdata_error = None;
+                    if let GameState::Loaded(state) = &mut self.game_state {
+                        state.rando_data.retain_rooms(&self.map_data);
+                        state.rebuild_graph(&self.map_data);
+                        if !self.map_data.rooms.contains_key(&state.current_room) {
+                            eprintln!(
+                                "Current room {} is missing from the map data",
+                                state.current_room
+                            );
+                        }
+                    }
+                },
+                Some(Err(e)) => {
+                    eprintln!("Failed to reload map data: {e}");
+                    self.mapdata_error = Some(e);
+                },
+                None => {},
             }
         }
+    }
 
-        // update with randomized data
-        for obj in rando_ctx["transitionPlacements"].members() {
-            let src = obj["Source"]["Name"].as_str().unwrap().into();
-            let dst = obj["Target"]["Name"].as_str().unwrap().into();
-            transition_map.insert(src, dst);
-        }
-
-        let mut visited_transitions = HashSet::new();
-        for (src, dst) in rando_data["TrackerData"]["visitedTransitions"].entries() {
-            visited_transitions.insert(src.into());
-            visited_transitions.insert(dst.as_str().unwrap().into());
+    fn load_save(&mut self, mut save: SaveData) {
+        self.save_arrangement();
+        if let (Some(dir), Some(seed)) = (&self.arrangement_dir, save.seed) {
+            match Arrangement::load(dir, seed) {
+                Ok(Some(arrangement)) => {
+                    println!("Restored room arrangement for seed {seed}");
+                    arrangement.apply(&mut save.rando_data, &save.current_room);
+                    save.rando_data.retain_rooms(&self.map_data);
+                },
+                Ok(None) => {},
+                Err(e) => eprintln!("Failed to load room arrangement: {e}"),
+            }
         }
 
+        let graph = Graph::new(&self.map_data, &save.rando_data, self.settings.path_costs);
         self.game_state = GameState::Loaded(LoadedState {
-            current_room: rando_ctx["StartDef"]["SceneName"]
-                .as_str()
-                .expect(
-                    format!(
-                        "Missing/Invalid field 'rando_ctx.StartDef.SceneName': {}",
-                        rando_ctx["StartDef"]["SceneName"]
-                    )
-                    .as_str(),
-                )
-                .into(),
+            current_room: save.current_room,
+            seed: save.seed,
             player_x: 0.0,
             player_y: 0.0,
-            rando_data: RandoData {
-                transition_map,
-                visited_transitions,
-                room_positions: HashMap::new(),
-            },
-            camera: Camera { x: 0.0, y: 0.0, target: CameraTarget::Player },
+            rando_data: save.rando_data,
+            camera: Camera::new(CameraTarget::Player),
             hovered_room: None,
             selected_room: None,
             dragging_room: false,
+            view: View::Local,
+            room_grid: RoomGrid::default(),
+            graph,
         });
-
-        Ok(())
     }
 
-    #[allow(clippy::too_many_lines)] // TODO
-    fn update_room_positions(&mut self) {
-        if let GameState::Loaded(state) = &mut self.game_state {
-            let rough_factor = 1.0
-                - (Instant::now()
-                    .saturating_duration_since(self.last_transition_time)
-                    .as_secs_f32()
-                    / 1.0
-                    - 0.5)
-                    .powi(2)
-                    .clamp(0.0, 1.0);
-            // let rough_factor = 1.0;
-
-            #[allow(clippy::needless_collect)] // actually needed
-            let v: Vec<_> = state.rando_data.room_positions.keys().cloned().collect();
-            for key in v {
-                if key == state.current_room {
-                    let (this_x, this_y) = state.rando_data.room_positions.get_mut(&key).unwrap();
-                    *this_x = 0.0;
-                    *this_y = 0.0;
-                } else if state.selected_room.as_ref() == Some(&key) && state.dragging_room {
-                    // don't move
-                } else if let Some((cur_room, other_rooms)) =
-                    self.map_data.rooms.split(&key).as_deref()
-                {
-                    let bounds = cur_room.calc_bounds();
-
-                    let (this_x, this_y) = *state.rando_data.room_positions.get(&key).unwrap();
-
-                    let mut move_x = 0.0;
-                    let mut move_y = 0.0;
-                    // let mut i = 0;
-
-                    // try to line up transitions
-                    for (k, tr) in &cur_room.transitions {
-                        let transition = format!("{}[{k}]", key);
-                        if state.rando_data.visited_transitions.contains(&transition) {
-                            if let Some((to_room, to_transition_k)) =
-                                Transition::get_transition_info(
-                                    state
-                                        .rando_data
-                                        .transition_map
-                                        .get(&transition)
-                                        .unwrap_or(&transition),
-                                )
-                            {
-                                if state.rando_data.room_positions.contains_key(&to_room) {
-                                    if let Some(next_room) = other_rooms.get(&to_room) {
-                                        let next_bounds = next_room.calc_bounds();
-
-                                        if let Some(to_transition) =
-                                            next_room.transitions.get(&to_transition_k)
-                                        {
-                                            let (other_x, other_y) = *state
-                                                .rando_data
-                                                .room_positions
-                                                .get(&to_room)
-                                                .unwrap();
+    /// Saves the room positions and pinned rooms of the loaded seed, if it has one.
+    fn save_arrangement(&self) {
+        if let (Some(dir), GameState::Loaded(state)) = (&self.arrangement_dir, &self.game_state) {
+            if let Some(seed) = state.seed {
+                if let Err(e) = Arrangement::from_rando_data(&state.rando_data).save(dir, seed) {
+                    eprintln!("Failed to save room arrangement: {e}");
+                }
+            }
+        }
+    }
 
-                                            // move so src lines up with dst
-                                            let strength = 0.005 + 0.4 * rough_factor;
-                                            let mut strength_x;
-                                            let mut strength_y;
-
-                                            let dx = (-tr.x + to_transition.x) - this_x + other_x;
-                                            let dy = (-(bounds.h - tr.y)
-                                                + (next_bounds.h - to_transition.y))
-                                                - this_y
-                                                + other_y;
-
-                                            if (k.starts_with("right")
-                                                && to_transition_k.starts_with("left"))
-                                                || (k.starts_with("left")
-                                                    && to_transition_k.starts_with("right"))
-                                            {
-                                                strength_x =
-                                                    ((dx.abs() - 200.0) / 200.0).clamp(0.0, 0.5);
-                                                if (k.starts_with("right") && dx < 0.0)
-                                                    || (k.starts_with("left") && dx > 0.0)
-                                                {
-                                                    strength_x = 2.0;
-                                                }
-                                                strength_y = 2.0;
-                                            } else if (k.starts_with("top")
-                                                && to_transition_k.starts_with("bot"))
-                                                || (k.starts_with("bot")
-                                                    && to_transition_k.starts_with("top"))
-                                            {
-                                                strength_x = 2.0;
-                                                strength_y =
-                                                    ((dy.abs() - 200.0) / 200.0).clamp(0.0, 0.5);
-                                                if (k.starts_with("top") && dy > 0.0)
-                                                    || (k.starts_with("bot") && dy < 0.0)
-                                                {
-                                                    strength_y = 2.0;
-                                                }
-                                            } else {
-                                                strength_x =
-                                                    ((dx.abs() - 400.0) / 400.0).clamp(0.0, 0.5);
-                                                strength_y =
-                                                    ((dy.abs() - 400.0) / 400.0).clamp(0.0, 0.5);
-                                            }
+    fn load_save_file(&mut self, path: impl AsRef<Path>) -> Result<(), String> {
+        let save = save::load_save_file(&path, &self.map_data)?;
+        self.load_save(save);
+        println!("Loaded save from {}", path.as_ref().display());
+        Ok(())
+    }
 
-                                            move_x += dx * strength * strength_x;
-                                            move_y += dy * strength * strength_y;
+    /// Where a window position is on the map, in map units.
+    fn window_to_map(&self, ctx: &Context, pos: impl Into<Point2<f32>>) -> Option<[f32; 2]> {
+        let pos = pos.into();
 
-                                            // i += 1;
-                                        }
-                                    }
-                                }
-                            }
-                        }
-                    }
+        if let GameState::Loaded(state) = &self.game_state {
+            let mut transform = TransformStack::new();
+            push_camera(&mut transform, ctx, &state.camera);
 
-                    // remove intersections
-                    for (other_key, other_room) in other_rooms.iter() {
-                        if state.rando_data.room_positions.contains_key(other_key) {
-                            let other_bounds = other_room.calc_bounds();
-                            let (other_x, other_y) =
-                                *state.rando_data.room_positions.get(other_key).unwrap();
-
-                            let mut tr_my_bounds = bounds; // copy
-                            tr_my_bounds.translate([this_x, this_y + bounds.h]);
-                            tr_my_bounds.inflate(10.0);
-
-                            let mut tr_other_bounds = other_bounds; // copy
-                            tr_other_bounds.translate([other_x, other_y + other_bounds.h]);
-                            tr_other_bounds.inflate(10.0);
-
-                            if tr_my_bounds.overlaps(&tr_other_bounds) {
-                                let o_left = tr_my_bounds.left().max(tr_other_bounds.left());
-                                let o_top = tr_my_bounds.top().max(tr_other_bounds.top());
-                                let o_right = tr_my_bounds.right().min(tr_other_bounds.right());
-                                let o_bottom = tr_my_bounds.bottom().min(tr_other_bounds.bottom());
-                                let overlap_rect =
-                                    Rect::new(o_left, o_top, o_right - o_left, o_bottom - o_top);
-
-                                move_x += (tr_my_bounds.center().x - overlap_rect.center().x)
-                                    * 0.00005
-                                    * overlap_rect.w
-                                    * overlap_rect.h
-                                    * (1.0 - rough_factor);
-                                move_y += (tr_my_bounds.center().y - overlap_rect.center().y)
-                                    * 0.00005
-                                    * overlap_rect.w
-                                    * overlap_rect.h
-                                    * (1.0 - rough_factor);
-                            }
+            let (x, y) = transform.inv_transform((pos.x, pos.y));
+            return Some([x, y]);
+        }
 
-                            // wide area
-
-                            let mut tr_my_bounds = bounds; // copy
-                            tr_my_bounds.translate([this_x, this_y + bounds.h]);
-                            tr_my_bounds.inflate(25.0);
-
-                            let mut tr_other_bounds = other_bounds; // copy
-                            tr_other_bounds.translate([other_x, other_y + other_bounds.h]);
-                            tr_other_bounds.inflate(25.0);
-
-                            if tr_my_bounds.overlaps(&tr_other_bounds) {
-                                let o_left = tr_my_bounds.left().max(tr_other_bounds.left());
-                                let o_top = tr_my_bounds.top().max(tr_other_bounds.top());
-                                let o_right = tr_my_bounds.right().min(tr_other_bounds.right());
-                                let o_bottom = tr_my_bounds.bottom().min(tr_other_bounds.bottom());
-                                let overlap_rect =
-                                    Rect::new(o_left, o_top, o_right - o_left, o_bottom - o_top);
-
-                                move_x += (tr_my_bounds.center().x - tr_other_bounds.center().x)
-                                    * 0.0000005
-                                    * overlap_rect.w
-                                    * overlap_rect.h
-                                    * (1.0 - rough_factor);
-                                move_y += (tr_my_bounds.center().y - tr_other_bounds.center().y)
-                                    * 0.0000005
-                                    * overlap_rect.w
-                                    * overlap_rect.h
-                                    * (1.0 - rough_factor);
-                            }
-                        }
-                    }
+        None
+    }
 
-                    let (this_x, this_y) = state.rando_data.room_positions.get_mut(&key).unwrap();
-                    *this_x = (*this_x + move_x.clamp(-100.0, 100.0)).clamp(-1000.0, 1000.0);
-                    *this_y = (*this_y + move_y.clamp(-100.0, 100.0)).clamp(-1000.0, 1000.0);
-                }
-            }
-        }
+    /// Whether the mouse moved past [`CLICK_SLOP`] since the last button press.
+    fn moved_since_click(&self, x: f32, y: f32) -> bool {
+        (self.click_start_x - x).abs() > CLICK_SLOP || (self.click_start_y - y).abs() > CLICK_SLOP
     }
 
     fn get_room_at_window_position(
         &self,
         ctx: &Context,
         pos: impl Into<Point2<f32>>,
-    ) -> Option<&String> {
-        let pos = pos.into();
-
+    ) -> Option<String> {
+        let point = self.window_to_map(ctx, pos)?;
         if let GameState::Loaded(state) = &self.game_state {
-            let mut transform = TransformStack::new();
+            return state.room_at(point).cloned();
+        }
 
-            transform.push();
-            transform.translate(
-                graphics::window(ctx).inner_size().width as f32 / 2.0,
-                graphics::window(ctx).inner_size().height as f32 / 2.0,
-            );
-            transform.translate(-state.camera.x, -state.camera.y);
+        None
+    }
 
-            for (room_key, (x, y)) in &state.rando_data.room_positions {
-                if let Some(cur_room) = self.map_data.rooms.get(room_key) {
-                    transform.push();
+    /// Tooltip for the room, transition or item under the mouse.
+    fn show_map_tooltip(&self, ctx: &Context, egui_ctx: &egui::Context) {
+        let GameState::Loaded(state) = &self.game_state else {
+            return;
+        };
+        if egui_ctx.is_pointer_over_area() || state.dragging_room {
+            return;
+        }
 
-                    let mut bounds = cur_room.calc_bounds();
-                    let orig_h = bounds.h;
-                    bounds.inflate(5.0);
+        let Some(point) = self.window_to_map(ctx, ggez::input::mouse::position(ctx)) else {
+            return;
+        };
+        let Some((key, local)) = state.room_point_at(&self.map_data, point) else {
+            return;
+        };
+        if let Some(room) = self.map_data.rooms.get(key) {
+            egui::show_tooltip_at_pointer(egui_ctx, egui::Id::new("map_hover"), |ui| {
+                room.fill_tooltip_egui(
+                    ui,
+                    key,
+                    local,
+                    &self.map_data,
+                    &state.rando_data,
+                    &self.settings,
+                );
+            });
+        }
+    }
 
-                    transform.translate(*x, *y);
+    /// Finds rooms by their key, name, area or locations, clicking one selects it and moves
+    /// the camera to it.
+    fn fill_search_egui(&mut self, ui: &mut egui::Ui) {
+        if ui.text_edit_singleline(&mut self.search_query).changed() {
+            self.search_results =
+                search::search_rooms(&self.map_data, &self.search_query, SEARCH_RESULTS);
+        }
 
-                    let (rel_x, rel_y) = transform.inv_transform((pos.x, pos.y));
+        let GameState::Loaded(state) = &mut self.game_state else {
+            return;
+        };
+        if self.search_results.is_empty() {
+            return;
+        }
 
-                    if bounds.contains([rel_x, rel_y - orig_h]) {
-                        return Some(room_key);
+        // rooms that haven't been reached can't be shown on the map
+        let reachable = state.graph.reachable_rooms(&state.current_room);
+        let mut clicked = None;
+        egui::ScrollArea::vertical()
+            .max_height(300.0)
+            .show(ui, |ui| {
+                for result in &self.search_results {
+                    let Some(room) = self.map_data.rooms.get(&result.room) else {
+                        continue;
+                    };
+                    let name = room.name.as_deref().unwrap_or(&result.room);
+                    let mut text = format!("{name} ({})", result.room);
+                    let matched: Vec<&str> = result
+                        .matched
+                        .iter()
+                        .map(String::as_str)
+                        .filter(|&field| field != name)
+                        .collect();
+                    if !matched.is_empty() {
+                        text.push_str("\n    ");
+                        text.push_str(&matched.join(", "));
                     }
 
-                    transform.pop();
+                    let selected = state.selected_room.as_ref() == Some(&result.room);
+                    let mut response = ui
+                        .add_enabled(
+                            reachable.contains(&result.room),
+                            egui::SelectableLabel::new(selected, text),
+                        )
+                        .on_disabled_hover_text("not reached yet");
+                    if state.view == View::Local && !state.visible_rooms().contains(&result.room) {
+                        response = response
+                            .on_hover_text("not in the local view, switches to the world map");
+                    }
+                    if response.clicked() {
+                        clicked = Some(result.room.clone());
+                    }
                 }
-            }
+            });
 
-            transform.pop();
+        if let Some(key) = clicked {
+            // the local view only shows the rooms around the current one
+            if state.view == View::Local && !state.visible_rooms().contains(&key) {
+                state.set_view(View::World, &mut self.vanilla_layout);
+            }
+            state.camera.target = CameraTarget::Room(key.clone());
+            state.selected_room = Some(key);
         }
-
-        None
     }
 
-    #[allow(clippy::ptr_arg)]
-    fn find_path(&self, src: &String, dst: &String) -> Option<Vec<String>> {
-        if let GameState::Loaded(state) = &self.game_state {
-            let mut dist_from_src: HashMap<String, f32> = HashMap::new();
-            let mut prev_transition: HashMap<String, String> = HashMap::new();
-
-            dist_from_src.insert(src.clone(), 0.0);
-
-            let mut unvisited: Vec<String> = self.map_data.rooms.keys().cloned().collect();
-
-            while let Some((idx, _)) = unvisited
-                .iter()
-                .enumerate()
-                .filter(|(_, r)| dist_from_src.contains_key(*r))
-                .min_by(|a, b| {
-                    dist_from_src
-                        .get(a.1)
-                        .unwrap()
-                        .partial_cmp(dist_from_src.get(b.1).unwrap())
-                        .unwrap()
-                })
-            {
-                let visiting = unvisited.remove(idx);
-                // println!("visiting {visiting}");
-
-                if &visiting == dst {
-                    let mut path = Vec::new();
-                    let mut prev_room = visiting;
-
-                    while let Some(prev_tr) = prev_transition.get(&prev_room) {
-                        path.push(prev_tr.clone());
-                        prev_room = Transition::get_transition_info(prev_tr).unwrap().0;
+    fn fill_main_egui(&mut self, ui: &mut egui::Ui, ctx: &mut Context) {
+        match &self.source {
+            MessageSource::Live(connection) => {
+                let (color, status) = match connection.status() {
+                    ConnectionStatus::Connecting { attempt } => (
+                        egui::Color32::YELLOW,
+                        format!("Connecting... (attempt {attempt})"),
+                    ),
+                    ConnectionStatus::Connected => (egui::Color32::GREEN, "Connected".into()),
+                    ConnectionStatus::Waiting { attempt, retry_at, error } => {
+                        let secs = retry_at
+                            .saturating_duration_since(Instant::now())
+                            .as_secs_f32();
+                        let status = format!("Disconnected, retry #{attempt} in {secs:.1}s");
+                        match error {
+                            Some(error) => (egui::Color32::RED, format!("{status} ({error})")),
+                            None => (egui::Color32::RED, status),
+                        }
+                    },
+                    ConnectionStatus::Closed => (egui::Color32::GRAY, "Closed".into()),
+                };
+                ui.horizontal(|ui| {
+                    ui.colored_label(color, "\u{23FA}");
+                    ui.label(status);
+                });
+
+                ui.horizontal(|ui| {
+                    ui.text_edit_singleline(&mut self.settings.ws_url);
+                    let text = if self.settings.ws_url == connection.url() {
+                        "reconnect"
+                    } else {
+                        "connect"
+                    };
+                    if ui.button(text).clicked() {
+                        connection.set_url(self.settings.ws_url.clone());
                     }
+                });
+            },
+            MessageSource::Replay { replay, path } => {
+                let (sent, total) = replay.progress();
+                ui.label(format!("Replaying {path}: {sent}/{total}"));
+            },
+        }
 
-                    path.reverse();
-                    return Some(path);
-                }
-
-                let room = self.map_data.rooms.get(&visiting).unwrap();
-
-                let dist_to_cur = *dist_from_src.get(&visiting).unwrap();
+        if let Some(watcher) = &self.mapdata_watcher {
+            ui.label(format!("Map data: {} (watching)", watcher.path.display()));
+            if let Some(e) = &self.mapdata_error {
+                ui.colored_label(egui::Color32::RED, e);
+            }
+        }
 
-                for tr_key in room.transitions.keys() {
-                    let cost = dist_to_cur + 1.0;
+        if let GameState::Loaded(state) = &mut self.game_state {
+            let mut view = state.view;
+            ui.horizontal(|ui| {
+                ui.radio_value(&mut view, View::Local, "local");
+                ui.radio_value(&mut view, View::World, "world map (M)");
+                ui.radio_value(&mut view, View::Vanilla, "vanilla map (V)");
+            });
+            if view != state.view {
+                state.set_view(view, &mut self.vanilla_layout);
+            }
 
-                    let transition = format!("{}[{tr_key}]", visiting);
-                    if state.rando_data.visited_transitions.contains(&transition) {
-                        if let Some((to_room, _to_transition)) = Transition::get_transition_info(
-                            state
-                                .rando_data
-                                .transition_map
-                                .get(&transition)
-                                .unwrap_or(&transition),
-                        ) {
-                            if unvisited.contains(&to_room) {
-                                if let Some(v) = dist_from_src.get(&to_room).copied() {
-                                    if cost < v {
-                                        dist_from_src.insert(to_room.clone(), cost);
-                                        prev_transition.insert(to_room, transition);
-                                    }
-                                } else {
-                                    dist_from_src.insert(to_room.clone(), cost);
-                                    prev_transition.insert(to_room, transition);
-                                }
-                            }
-                        }
-                    }
+            let mut destination = None;
+            ui.horizontal(|ui| {
+                ui.label("path to the nearest");
+                if ui.button("bench").clicked() {
+                    destination = Some(Destination::Bench);
+                }
+                if ui.button("stag").clicked() {
+                    destination = Some(Destination::Stag);
                 }
+                if ui.button("unvisited transition").clicked() {
+                    destination = Some(Destination::UnvisitedTransition);
+                }
+            });
+            if let Some(destination) = destination {
+                let src = state.selected_room.as_ref().unwrap_or(&state.current_room);
+                let route = state.graph.nearest(&self.map_data, src, destination);
+                self.path_target = route.as_ref().map(|route| route.room.clone());
+                self.highlight_path = route.map(|route| route.path);
             }
         }
 
-        None
+        ui.checkbox(
+            &mut self.settings.show_spoilers,
+            "show spoilers (item placements)",
+        );
+
+        ui.collapsing("open save file", |ui| {
+            ui.horizontal(|ui| {
+                ui.text_edit_singleline(&mut self.settings.save_path);
+                if ui.button("load").clicked() {
+                    let path = self.settings.save_path.clone();
+                    self.save_error = self.load_save_file(path).err();
+                }
+            });
+            if let Some(e) = &self.save_error {
+                ui.colored_label(egui::Color32::RED, e);
+            }
+        });
+
+        ui.collapsing("messages", |ui| {
+            self.inbox_stats.fill_debug_egui(ui, self.inbox.depth());
+        });
+
+        if ui.button("quit").clicked() {
+            ggez::event::quit(ctx);
+        }
     }
 }
 
 impl event::EventHandler<ggez::GameError> for MainState {
     fn update(&mut self, ctx: &mut Context) -> GameResult {
         let egui_ctx = self.egui_backend.ctx();
-        self.egui_ctx = Some((&*egui_ctx).clone());
-        egui::Window::new("Rusty Map View").show(&egui_ctx, |ui| {
-            if ui.button("quit").clicked() {
-                ggez::event::quit(ctx);
+        self.egui_ctx = Some((*egui_ctx).clone());
+        egui::Window::new("Rusty Map View").show(&egui_ctx, |ui| self.fill_main_egui(ui, ctx));
+
+        if let GameState::Loaded(state) = &mut self.game_state {
+            let key = state.selected_room.as_ref().unwrap_or(&state.current_room);
+            if let Some(room) = self.map_data.rooms.get(key) {
+                egui::Window::new("Room").show(&egui_ctx, |ui| {
+                    room.fill_info_egui(
+                        ui,
+                        key,
+                        &state.rando_data,
+                        &self.map_data.areas,
+                        self.settings.show_spoilers,
+                    );
+
+                    if let Some(pinned_rooms) = state.view.pinned_rooms_mut(&mut state.rando_data) {
+                        let mut pinned = pinned_rooms.contains(key);
+                        if ui.checkbox(&mut pinned, "pinned").changed() {
+                            if pinned {
+                                pinned_rooms.insert(key.clone());
+                            } else {
+                                pinned_rooms.remove(key);
+                            }
+                        }
+                    }
+                });
             }
-        });
+        }
+
+        egui::Window::new("Search").show(&egui_ctx, |ui| self.fill_search_egui(ui));
+
+        self.show_map_tooltip(ctx, &egui_ctx);
 
         egui::Window::new("All Settings").show(&egui_ctx, |ui| {
             self.settings.fill_debug_egui(ui);
+            ui.collapsing("item pools", |ui| {
+                self.settings
+                    .fill_pools_egui(ui, self.map_data.item_pools());
+            });
         });
 
         self.pos_x = self.pos_x % 800.0 + 1.0;
 
-        if let Ok(msg) = self.recv.try_recv() {
-            self.on_message(&msg, ctx)?;
+        self.reload_mapdata();
+
+        let batch = self.inbox.drain();
+        self.inbox_stats.record_batch(&batch, Instant::now());
+
+        let mut messages: Vec<Message> = batch
+            .iter()
+            .filter_map(|received| match Message::try_from(&received.json) {
+                Ok(msg) => Some(msg),
+                Err(e) => {
+                    eprintln!("Skipping message: {e}");
+                    None
+                },
+            })
+            .collect();
+        self.inbox_stats.coalesced += message::coalesce_positions(&mut messages);
+
+        for msg in messages {
+            self.on_message(msg, ctx)?;
         }
 
-        self.update_room_positions();
+        if let GameState::Loaded(state) = &mut self.game_state {
+            match state.view {
+                View::Local => {
+                    let rough_factor = layout::rough_factor(
+                        Instant::now().saturating_duration_since(self.last_transition_time),
+                    );
+                    layout::update_room_positions(
+                        &self.map_data,
+                        state,
+                        self.settings.depth,
+                        rough_factor,
+                        self.settings.area_pull,
+                    );
+                },
+                View::World => {
+                    layout::update_world_positions(&self.map_data, state, self.settings.area_pull);
+                },
+                // fixed, but laid out again after the map data is reloaded
+                View::Vanilla => {
+                    layout::fill_vanilla_positions(&mut state.rando_data, &mut self.vanilla_layout);
+                },
+            }
+        }
 
         if let GameState::Loaded(state) = &mut self.game_state {
+            state.set_path_costs(&self.map_data, self.settings.path_costs);
+            state.update_room_grid(&self.map_data, self.settings.depth);
             state.update(&self.map_data);
         }
 
@@ -657,67 +682,44 @@ impl event::EventHandler<ggez::GameError> for MainState {
             DrawParam::default().dest([self.pos_x, 380.0]),
         )?;
 
-        let hovered_room = self
-            .get_room_at_window_position(ctx, ggez::input::mouse::position(ctx))
-            .cloned();
+        let hovered_room = self.get_room_at_window_position(ctx, ggez::input::mouse::position(ctx));
 
         if let GameState::Loaded(state) = &mut self.game_state {
             state.hovered_room = hovered_room;
 
             let mut transform = TransformStack::new();
 
-            let mut render_rooms = BTreeSet::new();
-            render_rooms.insert(state.current_room.clone());
-            let depth = self.settings.depth;
-            for _ in 0..depth {
-                for key in render_rooms.clone() {
-                    if let Some(room) = self.map_data.rooms.get(&key) {
-                        for k in room.transitions.keys() {
-                            let transition = format!("{}[{k}]", key);
-                            if state.rando_data.visited_transitions.contains(&transition) {
-                                if let Some((to_room, _to_transition)) =
-                                    Transition::get_transition_info(
-                                        state
-                                            .rando_data
-                                            .transition_map
-                                            .get(&transition)
-                                            .unwrap_or(&transition),
-                                    )
-                                {
-                                    // if to_room == "Fungus1_19" || to_room == "Mines_20" {
-                                    render_rooms.insert(to_room);
-                                    // }
-                                }
-                            }
-                        }
-                    }
-                }
-            }
+            let render_rooms = state.visible_rooms();
 
             transform.push();
-            transform.translate(
-                graphics::window(ctx).inner_size().width as f32 / 2.0,
-                graphics::window(ctx).inner_size().height as f32 / 2.0,
-            );
-            transform.translate(-state.camera.x, -state.camera.y);
+            push_camera(&mut transform, ctx, &state.camera);
             // transform.scale(2.0, 2.0);
             // if let Some(cur_room) = self.map_data.rooms.get(&state.current_room) {
-            //     let bounds = cur_room.calc_bounds();
+            //     let bounds = cur_room.bounds();
             //     transform.translate(-bounds.center().x, -(bounds.h + bounds.center().y));
             // }
 
-            for key in &render_rooms {
+            if self.settings.draw_area_hulls {
+                area::draw_area_hulls(
+                    ctx,
+                    &transform,
+                    &self.map_data,
+                    render_rooms,
+                    state.positions(),
+                )?;
+            }
+
+            for key in render_rooms {
+                // rooms the layout hasn't placed yet aren't drawn
+                let Some(&(x, y)) = state.positions().get(key) else {
+                    continue;
+                };
                 if let Some((cur_room, other_rooms)) = self.map_data.rooms.split(key).as_deref_mut()
                 {
                     transform.push();
 
-                    let bounds = cur_room.calc_bounds();
+                    let bounds = cur_room.bounds();
 
-                    let (x, y) = *state
-                        .rando_data
-                        .room_positions
-                        .entry(key.clone())
-                        .or_insert_with(|| (0.0, 0.0));
                     transform.translate(x, y);
 
                     cur_room.draw(
@@ -728,12 +730,12 @@ impl event::EventHandler<ggez::GameError> for MainState {
                         &self.asset_cache,
                         state.hovered_room.as_ref().map(|k| k == key),
                         state.selected_room.as_ref().map(|k| k == key),
-                        &self.highlight_path,
+                        self.highlight_path.as_deref(),
                         &self.settings,
                     )?;
 
                     for (k, tr) in &cur_room.transitions {
-                        let transition = format!("{}[{k}]", key);
+                        let transition = format!("{key}[{k}]");
                         if state.rando_data.visited_transitions.contains(&transition) {
                             if let Some((to_room, to_transition_key)) =
                                 Transition::get_transition_info(
@@ -744,9 +746,11 @@ impl event::EventHandler<ggez::GameError> for MainState {
                                         .unwrap_or(&transition),
                                 )
                             {
-                                if state.rando_data.room_positions.contains_key(&to_room) {
+                                if render_rooms.contains(&to_room)
+                                    && state.positions().contains_key(&to_room)
+                                {
                                     if let Some(next_room) = other_rooms.get(&to_room) {
-                                        let next_bounds = next_room.calc_bounds();
+                                        let next_bounds = next_room.bounds();
 
                                         if let Some(to_transition) =
                                             next_room.transitions.get(&to_transition_key)
@@ -755,14 +759,10 @@ impl event::EventHandler<ggez::GameError> for MainState {
                                             // dx += ((-tr.x + to_transition.x) - this_x) * 0.0025;
                                             // dy += ((-(bounds.h - tr.y) + (next_bounds.h - to_transition.y)) - this_y) * 0.0025;
 
-                                            let (x2, y2) = state
-                                                .rando_data
-                                                .room_positions
-                                                .get(&to_room)
-                                                .unwrap();
+                                            let (x2, y2) = state.positions().get(&to_room).unwrap();
 
                                             let to_transition_id =
-                                                format!("{}[{to_transition_key}]", to_room);
+                                                format!("{to_room}[{to_transition_key}]");
 
                                             let mut color =
                                                 graphics::Color::from_rgba(64, 64, 192, 127);
@@ -810,16 +810,14 @@ impl event::EventHandler<ggez::GameError> for MainState {
                 }
             }
 
-            if let Some(cur_room) = self.map_data.rooms.get(&state.current_room) {
-                let bounds = cur_room.calc_bounds();
+            if let (Some(cur_room), Some(&(x, y))) = (
+                self.map_data.rooms.get(&state.current_room),
+                state.positions().get(&state.current_room),
+            ) {
+                let bounds = cur_room.bounds();
 
                 transform.push();
 
-                let (x, y) = *state
-                    .rando_data
-                    .room_positions
-                    .entry(state.current_room.clone())
-                    .or_insert_with(|| (0.0, 0.0));
                 transform.translate(x, y);
 
                 transform.push();
@@ -868,7 +866,7 @@ impl event::EventHandler<ggez::GameError> for MainState {
             );
 
             if let Some(path) = &self.highlight_path {
-                graphics::Text::new(format!("Path: {:?}", path))
+                graphics::Text::new(format!("Path: {path:?}"))
                     .draw(ctx, DrawParam::default().dest([8.0, 60.0]))?;
             }
         }
@@ -901,12 +899,10 @@ impl event::EventHandler<ggez::GameError> for MainState {
     }
 
     fn quit_event(&mut self, _ctx: &mut Context) -> bool {
-        if self.shutdown_thread.is_some() {
-            println!("Closing connection...");
-            self.shutdown.send(()).unwrap();
-            self.shutdown_thread.take().unwrap().join().unwrap();
-            self.listen_thread.take().unwrap().join().unwrap();
-            println!("Done");
+        self.save_arrangement();
+        match &mut self.source {
+            MessageSource::Live(connection) => connection.shutdown(),
+            MessageSource::Replay { replay, .. } => replay.shutdown(),
         }
         false
     }
@@ -923,13 +919,14 @@ impl event::EventHandler<ggez::GameError> for MainState {
         if !self
             .egui_ctx
             .as_ref()
-            .map_or(false, egui::Context::wants_pointer_input)
+            .is_some_and(egui::Context::wants_pointer_input)
         {
             self.click_start_x = x;
             self.click_start_y = y;
+            self.room_drag_started = false;
 
             if button == MouseButton::Left {
-                let hovered_room = self.get_room_at_window_position(ctx, [x, y]).cloned();
+                let hovered_room = self.get_room_at_window_position(ctx, [x, y]);
 
                 if let GameState::Loaded(state) = &mut self.game_state {
                     state.dragging_room = true;
@@ -937,31 +934,35 @@ impl event::EventHandler<ggez::GameError> for MainState {
                     state.hovered_room = hovered_room;
                     state.selected_room = state.hovered_room.clone();
                 }
-            } else if button == MouseButton::Right {
-                let hovered_room = self.get_room_at_window_position(ctx, [x, y]).cloned();
-
-                if let GameState::Loaded(state) = &mut self.game_state {
-                    state.hovered_room = hovered_room;
-                    self.path_target = state.hovered_room.clone();
-                    let src = state
-                        .selected_room
-                        .clone()
-                        .unwrap_or_else(|| state.current_room.clone());
-                    let dst = self.path_target.clone();
-                    self.highlight_path = dst.and_then(|dst| self.find_path(&src, &dst));
-
-                    // state.selected_room = state.hovered_room.clone();
-                    // if let Some(r) = state.hovered_room.clone() {
-                    //     state.camera.target = CameraTarget::Room(r);
-                    // }
-                }
             }
         }
     }
 
-    fn mouse_button_up_event(&mut self, _ctx: &mut Context, button: MouseButton, _x: f32, _y: f32) {
+    fn mouse_button_up_event(&mut self, ctx: &mut Context, button: MouseButton, x: f32, y: f32) {
         self.egui_backend.input.mouse_button_up_event(button);
 
+        // right dragging pans, so only a right click without moving picks a path target
+        if button == MouseButton::Right
+            && !self.moved_since_click(x, y)
+            && !self
+                .egui_ctx
+                .as_ref()
+                .is_some_and(egui::Context::wants_pointer_input)
+        {
+            let hovered_room = self.get_room_at_window_position(ctx, [x, y]);
+
+            if let GameState::Loaded(state) = &mut self.game_state {
+                state.hovered_room = hovered_room;
+                self.path_target = state.hovered_room.clone();
+                let src = state
+                    .selected_room
+                    .clone()
+                    .unwrap_or_else(|| state.current_room.clone());
+                let dst = self.path_target.clone();
+                self.highlight_path = dst.and_then(|dst| state.graph.find_path(&src, &dst));
+            }
+        }
+
         // if button == MouseButton::Left && (self.click_start_x - x).abs() <= 2.0 && (self.click_start_y - y).abs() <= 2.0 {
         //     let hovered_room = self.get_room_at_window_position(ctx, [x, y]).cloned();
 
@@ -975,32 +976,149 @@ impl event::EventHandler<ggez::GameError> for MainState {
         if !self
             .egui_ctx
             .as_ref()
-            .map_or(false, egui::Context::wants_pointer_input)
+            .is_some_and(egui::Context::wants_pointer_input)
         {
             if button == MouseButton::Left {
                 if let GameState::Loaded(state) = &mut self.game_state {
                     state.dragging_room = false;
+                    if std::mem::take(&mut self.room_drag_started)
+                        && state.selected_room.as_ref().is_some_and(|room| {
+                            state
+                                .pinned_rooms()
+                                .is_some_and(|pinned| pinned.contains(room))
+                        })
+                    {
+                        self.save_arrangement();
+                    }
                 }
             }
         }
     }
 
+    fn key_down_event(
+        &mut self,
+        _ctx: &mut Context,
+        keycode: KeyCode,
+        keymods: KeyMods,
+        _repeat: bool,
+    ) {
+        self.egui_backend.input.key_down_event(keycode, keymods);
+
+        if self
+            .egui_ctx
+            .as_ref()
+            .is_some_and(egui::Context::wants_keyboard_input)
+        {
+            return;
+        }
+
+        match keycode {
+            KeyCode::Escape => {
+                if let GameState::Loaded(state) = &mut self.game_state {
+                    state.selected_room = None;
+                }
+                self.path_target = None;
+                self.highlight_path = None;
+            },
+            KeyCode::M => {
+                if let GameState::Loaded(state) = &mut self.game_state {
+                    let view = if state.view == View::World {
+                        View::Local
+                    } else {
+                        View::World
+                    };
+                    state.set_view(view, &mut self.vanilla_layout);
+                }
+            },
+            KeyCode::V => {
+                if let GameState::Loaded(state) = &mut self.game_state {
+                    let view = if state.view == View::Vanilla {
+                        View::Local
+                    } else {
+                        View::Vanilla
+                    };
+                    state.set_view(view, &mut self.vanilla_layout);
+                }
+            },
+            KeyCode::F => {
+                if let GameState::Loaded(state) = &mut self.game_state {
+                    state.camera.target = CameraTarget::Player;
+                }
+            },
+            KeyCode::R => {
+                if let GameState::Loaded(state) = &mut self.game_state {
+                    state.camera.target = CameraTarget::PlayerRoom;
+                }
+            },
+            KeyCode::Key0 => {
+                if let GameState::Loaded(state) = &mut self.game_state {
+                    state.camera.zoom = 1.0;
+                }
+            },
+            _ => {},
+        }
+    }
+
+    fn mouse_wheel_event(&mut self, ctx: &mut Context, x: f32, y: f32) {
+        self.egui_backend.input.mouse_wheel_event(x, y);
+
+        if !self
+            .egui_ctx
+            .as_ref()
+            .is_some_and(egui::Context::wants_pointer_input)
+        {
+            if let GameState::Loaded(state) = &mut self.game_state {
+                let mouse = ggez::input::mouse::position(ctx);
+                let size = graphics::window(ctx).inner_size();
+                let cursor = (
+                    mouse.x - size.width as f32 / 2.0,
+                    mouse.y - size.height as f32 / 2.0,
+                );
+                state.camera.zoom_at(1.1_f32.powf(y), cursor);
+            }
+        }
+    }
+
+    fn text_input_event(&mut self, _ctx: &mut Context, character: char) {
+        self.egui_backend.input.text_input_event(character);
+    }
+
     fn mouse_motion_event(&mut self, ctx: &mut Context, x: f32, y: f32, dx: f32, dy: f32) {
         self.egui_backend.input.mouse_motion_event(x, y);
 
         if !self
             .egui_ctx
             .as_ref()
-            .map_or(false, egui::Context::wants_pointer_input)
+            .is_some_and(egui::Context::wants_pointer_input)
         {
+            // a click that jitters a little shouldn't move or pin the room
+            let (mut dx, mut dy) = (dx, dy);
+            if ggez::input::mouse::button_pressed(ctx, MouseButton::Left) && !self.room_drag_started
+            {
+                if !self.moved_since_click(x, y) {
+                    return;
+                }
+                self.room_drag_started = true;
+                // catch up with the movement inside the slop
+                (dx, dy) = (x - self.click_start_x, y - self.click_start_y);
+            }
+
             if let GameState::Loaded(state) = &mut self.game_state {
                 if ggez::input::mouse::button_pressed(ctx, MouseButton::Left) {
-                    if let Some(sel_room) = &state.selected_room {
-                        if let Some((x, y)) = state.rando_data.room_positions.get_mut(sel_room) {
-                            *x += dx;
-                            *y += dy;
+                    if let Some(sel_room) = state.selected_room.clone() {
+                        let zoom = state.camera.zoom;
+                        if let Some((room_x, room_y)) = state.positions_mut().get_mut(&sel_room) {
+                            *room_x += dx / zoom;
+                            *room_y += dy / zoom;
+                            if let Some(pinned_rooms) = state.pinned_rooms_mut() {
+                                pinned_rooms.insert(sel_room);
+                            }
                         }
                     }
+                } else if ggez::input::mouse::button_pressed(ctx, MouseButton::Middle)
+                    || ggez::input::mouse::button_pressed(ctx, MouseButton::Right)
+                {
+                    state.camera.pan(dx, dy);
                 }
             }
         }
@@ -1008,16 +1126,62 @@ impl event::EventHandler<ggez::GameError> for MainState {
 
     // TODO: not needed in ggez 0.8
     fn resize_event(&mut self, ctx: &mut Context, width: f32, height: f32) {
-        let rect = graphics::Rect::new(0.0, 0.0, width as f32, height as f32);
+        let rect = graphics::Rect::new(0.0, 0.0, width, height);
         graphics::set_screen_coordinates(ctx, rect).unwrap();
     }
 }
 
+/// Moves the origin of `transform` to where the camera is looking and applies its zoom.
+fn push_camera(transform: &mut TransformStack, ctx: &Context, camera: &Camera) {
+    transform.translate(
+        graphics::window(ctx).inner_size().width as f32 / 2.0,
+        graphics::window(ctx).inner_size().height as f32 / 2.0,
+    );
+    transform.scale(camera.zoom, camera.zoom);
+    transform.translate(-camera.x, -camera.y);
+}
+
+fn validate(args: &Args) -> ! {
+    let map_data = match &args.mapdata {
+        Some(path) => data::read_mapdata(path),
+        None => data::load_mapdata(data::EMBEDDED_MAPDATA),
+    };
+
+    match map_data {
+        Ok(map_data) => {
+            let report = data::validate::validate(&map_data);
+            println!("{report}");
+            std::process::exit(i32::from(report.has_errors()));
+        },
+        Err(e) => {
+            eprintln!("error: {e}");
+            std::process::exit(1);
+        },
+    }
+}
+
 pub fn main() -> GameResult {
+    let args = match Args::parse(std::env::args().skip(1)) {
+        Ok(ParseResult::Run(args)) => args,
+        Ok(ParseResult::Help) => {
+            println!("{}", args::USAGE);
+            return Ok(());
+        },
+        Err(e) => {
+            eprintln!("{e}\n\n{}", args::USAGE);
+            std::process::exit(2);
+        },
+    };
+
+    match args.command {
+        Command::Run => {},
+        Command::Validate => validate(&args),
+    }
+
     let cb = ggez::ContextBuilder::new("rusty-map-view", "PieKing1215")
         .window_setup(WindowSetup::default().title("rusty-map-view").vsync(false))
         .window_mode(WindowMode::default().resizable(true));
     let (mut ctx, event_loop) = cb.build()?;
-    let state = MainState::new(&mut ctx)?;
+    let state = MainState::new(&mut ctx, args)?;
     event::run(ctx, event_loop, state)
 }
@@ -0,0 +1,350 @@
+use std::fmt::Display;
+
+use json::JsonValue;
+
+/// A message sent by the `TangledMapView` mod.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Message {
+    LoadSave { data: JsonValue },
+    UnloadSave,
+    PlayerMove { new_room: String, x: f32, y: f32 },
+    PlayerPos { x: f32, y: f32 },
+    RevealTransition { to: String },
+    GetItem { item: String, location: String },
+    Asset { name: String, data: String },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MessageError {
+    NotAnObject,
+    MissingType,
+    UnknownType(String),
+    InvalidField {
+        message_type: &'static str,
+        field: &'static str,
+        value: String,
+    },
+}
+
+impl Display for MessageError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::NotAnObject => write!(f, "Message is not a JSON object"),
+            Self::MissingType => write!(f, "Message missing type"),
+            Self::UnknownType(t) => write!(f, "Unimplemented message type: {t}"),
+            Self::InvalidField { message_type, field, value } => {
+                write!(
+                    f,
+                    "{message_type}: Missing/Invalid field '{field}': {value}"
+                )
+            },
+        }
+    }
+}
+
+impl std::error::Error for MessageError {}
+
+impl Message {
+    /// The value of the `type` field this message is sent with.
+    #[must_use]
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            Self::LoadSave { .. } => "loadSave",
+            Self::UnloadSave => "unloadSave",
+            Self::PlayerMove { .. } => "playerMove",
+            Self::PlayerPos { .. } => "playerPos",
+            Self::RevealTransition { .. } => "revealTransition",
+            Self::GetItem { .. } => "getItem",
+            Self::Asset { .. } => "asset",
+        }
+    }
+}
+
+/// Removes `playerPos` messages that are superseded by a later one in the same batch,
+/// returning how many were removed.
+pub fn coalesce_positions(messages: &mut Vec<Message>) -> usize {
+    let before = messages.len();
+
+    let mut superseded = false;
+    let mut keep: Vec<bool> = messages
+        .iter()
+        .rev()
+        .map(|msg| match msg {
+            Message::PlayerPos { .. } => !std::mem::replace(&mut superseded, true),
+            // a room change (or a different save) needs the position it was sent with
+            Message::PlayerMove { .. } | Message::LoadSave { .. } | Message::UnloadSave => {
+                superseded = false;
+                true
+            },
+            _ => true,
+        })
+        .collect();
+    keep.reverse();
+
+    let mut keep = keep.into_iter();
+    messages.retain(|_| keep.next().unwrap());
+
+    before - messages.len()
+}
+
+struct Fields<'a> {
+    json: &'a JsonValue,
+    message_type: &'static str,
+}
+
+impl Fields<'_> {
+    fn error(&self, field: &'static str) -> MessageError {
+        MessageError::InvalidField {
+            message_type: self.message_type,
+            field,
+            value: self.json[field].dump(),
+        }
+    }
+
+    fn str(&self, field: &'static str) -> Result<String, MessageError> {
+        self.json[field]
+            .as_str()
+            .map(Into::into)
+            .ok_or_else(|| self.error(field))
+    }
+
+    fn f32(&self, field: &'static str) -> Result<f32, MessageError> {
+        self.json[field].as_f32().ok_or_else(|| self.error(field))
+    }
+}
+
+impl TryFrom<&JsonValue> for Message {
+    type Error = MessageError;
+
+    fn try_from(json: &JsonValue) -> Result<Self, Self::Error> {
+        if !json.is_object() {
+            return Err(MessageError::NotAnObject);
+        }
+
+        let message_type = json["type"].as_str().ok_or(MessageError::MissingType)?;
+        let fields = |message_type| Fields { json, message_type };
+
+        Ok(match message_type {
+            "loadSave" => {
+                let f = fields("loadSave");
+                if !json["data"].is_object() {
+                    return Err(f.error("data"));
+                }
+                Self::LoadSave { data: json["data"].clone() }
+            },
+            "unloadSave" => Self::UnloadSave,
+            "playerMove" => {
+                let f = fields("playerMove");
+                Self::PlayerMove {
+                    new_room: f.str("newRoom")?,
+                    x: f.f32("x")?,
+                    y: f.f32("y")?,
+                }
+            },
+            "playerPos" => {
+                let f = fields("playerPos");
+                Self::PlayerPos { x: f.f32("x")?, y: f.f32("y")? }
+            },
+            "revealTransition" => {
+                let f = fields("revealTransition");
+                Self::RevealTransition { to: f.str("to")? }
+            },
+            "getItem" => {
+                let f = fields("getItem");
+                Self::GetItem { item: f.str("item")?, location: f.str("location")? }
+            },
+            "asset" => {
+                let f = fields("asset");
+                Self::Asset { name: f.str("name")?, data: f.str("data")? }
+            },
+            t => return Err(MessageError::UnknownType(t.into())),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(source: &str) -> Result<Message, MessageError> {
+        Message::try_from(&json::parse(source).unwrap())
+    }
+
+    #[test]
+    fn load_save() {
+        let msg = parse(r#"{ "type": "loadSave", "data": { "playerData": {} } }"#).unwrap();
+        assert_eq!(
+            msg,
+            Message::LoadSave { data: json::object! { "playerData": {} } }
+        );
+        assert_eq!(msg.type_name(), "loadSave");
+    }
+
+    #[test]
+    fn unload_save() {
+        assert_eq!(
+            parse(r#"{ "type": "unloadSave" }"#),
+            Ok(Message::UnloadSave)
+        );
+    }
+
+    #[test]
+    fn player_move() {
+        assert_eq!(
+            parse(r#"{ "type": "playerMove", "newRoom": "Town", "x": 1.5, "y": -2 }"#),
+            Ok(Message::PlayerMove { new_room: "Town".into(), x: 1.5, y: -2.0 })
+        );
+    }
+
+    #[test]
+    fn player_pos() {
+        assert_eq!(
+            parse(r#"{ "type": "playerPos", "x": 10, "y": 20.25 }"#),
+            Ok(Message::PlayerPos { x: 10.0, y: 20.25 })
+        );
+    }
+
+    #[test]
+    fn reveal_transition() {
+        assert_eq!(
+            parse(r#"{ "type": "revealTransition", "to": "Town[left1]" }"#),
+            Ok(Message::RevealTransition { to: "Town[left1]".into() })
+        );
+    }
+
+    #[test]
+    fn get_item() {
+        assert_eq!(
+            parse(
+                r#"{ "type": "getItem", "item": "Mothwing_Cloak", "location": "Vengeful_Spirit" }"#
+            ),
+            Ok(Message::GetItem {
+                item: "Mothwing_Cloak".into(),
+                location: "Vengeful_Spirit".into(),
+            })
+        );
+    }
+
+    #[test]
+    fn asset() {
+        assert_eq!(
+            parse(r#"{ "type": "asset", "name": "pin_bench", "data": "aGk=" }"#),
+            Ok(Message::Asset { name: "pin_bench".into(), data: "aGk=".into() })
+        );
+    }
+
+    #[test]
+    fn not_an_object() {
+        assert_eq!(
+            parse(r#"["playerPos", 1, 2]"#),
+            Err(MessageError::NotAnObject)
+        );
+        assert_eq!(parse(r#""unloadSave""#), Err(MessageError::NotAnObject));
+    }
+
+    #[test]
+    fn missing_type() {
+        assert_eq!(
+            parse(r#"{ "x": 1, "y": 2 }"#),
+            Err(MessageError::MissingType)
+        );
+        assert_eq!(parse(r#"{ "type": 5 }"#), Err(MessageError::MissingType));
+    }
+
+    #[test]
+    fn unknown_type() {
+        assert_eq!(
+            parse(r#"{ "type": "playerDance" }"#),
+            Err(MessageError::UnknownType("playerDance".into()))
+        );
+    }
+
+    #[test]
+    fn invalid_fields() {
+        assert_eq!(
+            parse(r#"{ "type": "playerMove", "newRoom": "Town", "x": "1", "y": 2 }"#),
+            Err(MessageError::InvalidField {
+                message_type: "playerMove",
+                field: "x",
+                value: r#""1""#.into(),
+            })
+        );
+        assert_eq!(
+            parse(r#"{ "type": "getItem", "item": "Grub" }"#),
+            Err(MessageError::InvalidField {
+                message_type: "getItem",
+                field: "location",
+                value: "null".into(),
+            })
+        );
+        assert_eq!(
+            parse(r#"{ "type": "loadSave", "data": "{}" }"#),
+            Err(MessageError::InvalidField {
+                message_type: "loadSave",
+                field: "data",
+                value: r#""{}""#.into(),
+            })
+        );
+    }
+
+    #[test]
+    fn coalesce_keeps_the_last_position() {
+        let pos = |x| Message::PlayerPos { x, y: 0.0 };
+        let item = Message::GetItem { item: "Grub".into(), location: "Grub-Town".into() };
+        let moved = Message::PlayerMove { new_room: "Town".into(), x: 0.0, y: 0.0 };
+        let mut messages = vec![
+            pos(1.0),
+            item.clone(),
+            pos(2.0),
+            moved.clone(),
+            pos(3.0),
+            pos(4.0),
+            Message::UnloadSave,
+            pos(5.0),
+            pos(6.0),
+        ];
+
+        assert_eq!(coalesce_positions(&mut messages), 3);
+        assert_eq!(
+            messages,
+            [
+                item,
+                pos(2.0),
+                moved,
+                pos(4.0),
+                Message::UnloadSave,
+                pos(6.0)
+            ]
+        );
+    }
+
+    #[test]
+    fn coalesce_without_positions() {
+        let mut messages = vec![
+            Message::RevealTransition { to: "Town[left1]".into() },
+            Message::UnloadSave,
+        ];
+        let before = messages.clone();
+
+        assert_eq!(coalesce_positions(&mut messages), 0);
+        assert_eq!(messages, before);
+        assert_eq!(coalesce_positions(&mut Vec::new()), 0);
+    }
+
+    #[test]
+    fn error_messages() {
+        assert_eq!(
+            MessageError::InvalidField {
+                message_type: "playerPos",
+                field: "y",
+                value: "null".into(),
+            }
+            .to_string(),
+            "playerPos: Missing/Invalid field 'y': null"
+        );
+        assert_eq!(
+            MessageError::UnknownType("foo".into()).to_string(),
+            "Unimplemented message type: foo"
+        );
+    }
+}
@@ -0,0 +1,243 @@
+use std::{
+    fs::File,
+    io::{BufWriter, Write},
+    path::Path,
+    sync::{
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+        Arc,
+    },
+    thread::JoinHandle,
+    time::{Duration, Instant},
+};
+
+use json::JsonValue;
+
+use crate::inbox::InboxSender;
+
+/// Writes incoming messages to a JSON-lines file, one `{"t": <ms since start>, "msg": <message>}`
+/// object per line.
+pub struct Recorder {
+    writer: BufWriter<File>,
+    start: Instant,
+}
+
+impl Recorder {
+    pub fn create(path: impl AsRef<Path>) -> std::io::Result<Self> {
+        Ok(Self {
+            writer: BufWriter::new(File::create(path)?),
+            start: Instant::now(),
+        })
+    }
+
+    pub fn record(&mut self, msg: &JsonValue) -> std::io::Result<()> {
+        let t = self.start.elapsed().as_secs_f64() * 1000.0;
+        writeln!(self.writer, "{{\"t\":{t:.3},\"msg\":{}}}", msg.dump())?;
+        // flush every message so a crash still leaves a usable recording
+        self.writer.flush()
+    }
+}
+
+pub struct RecordedMessage {
+    pub time: Duration,
+    pub msg: JsonValue,
+}
+
+/// Parses a recording made by [`Recorder`].
+pub fn parse_recording(source: &str) -> Result<Vec<RecordedMessage>, String> {
+    source
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(i, line)| {
+            let line_no = i + 1;
+            let mut json = json::parse(line).map_err(|e| format!("line {line_no}: {e}"))?;
+            let t = json["t"]
+                .as_f64()
+                .ok_or_else(|| format!("line {line_no}: Missing/Invalid field 't'"))?;
+            if json["msg"].is_null() {
+                return Err(format!("line {line_no}: Missing field 'msg'"));
+            }
+
+            Ok(RecordedMessage {
+                time: Duration::from_secs_f64(t.max(0.0) / 1000.0),
+                msg: json["msg"].take(),
+            })
+        })
+        .collect()
+}
+
+pub fn read_recording(path: impl AsRef<Path>) -> Result<Vec<RecordedMessage>, String> {
+    let path = path.as_ref();
+    let source = std::fs::read_to_string(path).map_err(|e| format!("{}: {e}", path.display()))?;
+    parse_recording(&source).map_err(|e| format!("{}: {e}", path.display()))
+}
+
+/// Feeds a recording back into an inbox on a background thread, keeping the original timing.
+pub struct Replay {
+    total: usize,
+    progress: Arc<AtomicUsize>,
+    stop: Arc<AtomicBool>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl Replay {
+    /// `speed` scales the delays between messages, `0.0` sends everything at once.
+    /// Messages that would be due too far in the future to represent are never sent.
+    #[must_use]
+    pub fn start(messages: Vec<RecordedMessage>, speed: f32, send: InboxSender) -> Self {
+        let total = messages.len();
+        let progress = Arc::new(AtomicUsize::new(0));
+        let stop = Arc::new(AtomicBool::new(false));
+
+        let thread_progress = Arc::clone(&progress);
+        let thread_stop = Arc::clone(&stop);
+        let thread = std::thread::spawn(move || {
+            let start = Instant::now();
+            // don't replay the time spent waiting for the first message
+            let offset = messages.first().map_or(Duration::ZERO, |m| m.time);
+            for RecordedMessage { time, msg } in messages {
+                if speed > 0.0 {
+                    let delay = time.saturating_sub(offset).as_secs_f64() / f64::from(speed);
+                    let due = Duration::try_from_secs_f64(delay)
+                        .ok()
+                        .and_then(|delay| start.checked_add(delay));
+                    // sleep in small steps so stopping doesn't wait for a long gap
+                    while !thread_stop.load(Ordering::SeqCst)
+                        && due.is_none_or(|due| Instant::now() < due)
+                    {
+                        let left = due.map_or(Duration::MAX, |due| {
+                            due.saturating_duration_since(Instant::now())
+                        });
+                        std::thread::sleep(left.min(Duration::from_millis(50)));
+                    }
+                }
+
+                if thread_stop.load(Ordering::SeqCst) || !send.send(msg) {
+                    break;
+                }
+                thread_progress.fetch_add(1, Ordering::SeqCst);
+            }
+        });
+
+        Self { total, progress, stop, thread: Some(thread) }
+    }
+
+    /// (sent, total)
+    #[must_use]
+    pub fn progress(&self) -> (usize, usize) {
+        (self.progress.load(Ordering::SeqCst), self.total)
+    }
+
+    pub fn shutdown(&mut self) {
+        if let Some(thread) = self.thread.take() {
+            self.stop.store(true, Ordering::SeqCst);
+            thread.join().unwrap();
+        }
+    }
+}
+
+impl Drop for Replay {
+    fn drop(&mut self) {
+        self.shutdown();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{inbox, message::Message};
+
+    /// A short session: walking out of King's Pass into Dirtmouth and down the well.
+    const SESSION: &str = include_str!("../res/test/session.jsonl");
+
+    #[test]
+    fn recorder_round_trip() {
+        let path = std::env::temp_dir().join(format!(
+            "rusty-map-view-recording-{}.jsonl",
+            std::process::id()
+        ));
+        let messages = parse_recording(SESSION).unwrap();
+
+        let mut recorder = Recorder::create(&path).unwrap();
+        for message in &messages {
+            recorder.record(&message.msg).unwrap();
+        }
+        drop(recorder);
+        let result = read_recording(&path);
+        std::fs::remove_file(&path).unwrap();
+
+        let replayed = result.unwrap();
+        assert_eq!(replayed.len(), messages.len());
+        for (replayed, message) in replayed.iter().zip(&messages) {
+            assert_eq!(replayed.msg, message.msg);
+        }
+        assert!(replayed.windows(2).all(|w| w[0].time <= w[1].time));
+    }
+
+    #[test]
+    fn parse_session() {
+        let messages = parse_recording(SESSION).unwrap();
+        assert_eq!(messages.len(), 11);
+        assert_eq!(messages[4].time, Duration::from_micros(1_840_512));
+
+        let parsed: Vec<Message> = messages
+            .iter()
+            .map(|m| Message::try_from(&m.msg).unwrap())
+            .collect();
+        assert_eq!(
+            parsed[5],
+            Message::RevealTransition { to: "Town[left1]".into() }
+        );
+        assert_eq!(parsed.last(), Some(&Message::UnloadSave));
+    }
+
+    #[test]
+    fn parse_errors() {
+        assert_eq!(
+            parse_recording("{\"t\":0,\"msg\":{}}\nnot json")
+                .err()
+                .unwrap(),
+            "line 2: Unexpected character: o at (1:2)"
+        );
+        assert_eq!(
+            parse_recording("{\"msg\":{}}").err().unwrap(),
+            "line 1: Missing/Invalid field 't'"
+        );
+        assert_eq!(
+            parse_recording("{\"t\":1}").err().unwrap(),
+            "line 1: Missing field 'msg'"
+        );
+    }
+
+    #[test]
+    fn replay_at_full_speed() {
+        let (send, inbox) = inbox::inbox();
+        let mut replay = Replay::start(parse_recording(SESSION).unwrap(), 0.0, send);
+        let start = Instant::now();
+        while replay.progress().0 < 11 && start.elapsed() < Duration::from_secs(5) {
+            std::thread::sleep(Duration::from_millis(1));
+        }
+        replay.shutdown();
+
+        assert_eq!(replay.progress(), (11, 11));
+        let types: Vec<String> = inbox
+            .drain()
+            .into_iter()
+            .map(|r| r.json["type"].to_string())
+            .collect();
+        assert_eq!(types[..2], ["playerMove", "playerPos"]);
+        assert_eq!(types.len(), 11);
+    }
+
+    #[test]
+    fn replay_tiny_speed() {
+        // the delays overflow a `Duration`, the messages just never come
+        let (send, inbox) = inbox::inbox();
+        let mut replay = Replay::start(parse_recording(SESSION).unwrap(), 1e-30, send);
+        std::thread::sleep(Duration::from_millis(20));
+        replay.shutdown();
+
+        assert_eq!(replay.progress(), (1, 11));
+        assert_eq!(inbox.drain().len(), 1);
+    }
+}
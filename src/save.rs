@@ -0,0 +1,267 @@
+use std::{
+    collections::{HashMap, HashSet},
+    path::Path,
+};
+
+use json::JsonValue;
+
+use crate::data::{MapData, RandoData};
+
+/// What we need from a save to build a `LoadedState`.
+pub struct SaveData {
+    pub current_room: String,
+    /// `None` for saves without generation settings, so there's nothing to key a saved
+    /// [`Arrangement`](crate::arrangement::Arrangement) by.
+    pub seed: Option<i64>,
+    pub rando_data: RandoData,
+}
+
+/// Mod data is sent as JSON encoded strings, but saves on disk can have it inline.
+fn mod_data(value: &JsonValue, name: &str) -> Result<JsonValue, String> {
+    if let Some(raw_json) = value.as_str() {
+        json::parse(raw_json).map_err(|json_err| format!("{name}: {json_err}"))
+    } else if value.is_object() {
+        Ok(value.clone())
+    } else {
+        Err(format!("Missing {name}"))
+    }
+}
+
+/// Reads `itemPlacements` of the randomizer context into the items at each location.
+///
+/// Randomizer 4 saves each placement as its `RandoModItem` and `RandoModLocation`, both with
+/// a `Name` next to their definition and logic.
+fn item_placements(rando_ctx: &JsonValue) -> Result<HashMap<String, Vec<String>>, String> {
+    let mut placements: HashMap<String, Vec<String>> = HashMap::new();
+    for obj in rando_ctx["itemPlacements"].members() {
+        let item = obj["Item"]["Name"]
+            .as_str()
+            .ok_or_else(|| format!("Invalid item placement: {obj}"))?;
+        let location = obj["Location"]["Name"]
+            .as_str()
+            .ok_or_else(|| format!("Invalid item placement: {obj}"))?;
+        placements
+            .entry(location.into())
+            .or_default()
+            .push(item.into());
+    }
+    Ok(placements)
+}
+
+/// Builds the randomizer state out of the `data` of a `loadSave` message.
+pub fn load_save(data: &JsonValue, map_data: &MapData) -> Result<SaveData, String> {
+    let hk_ver = data["playerData"]["version"].as_str().ok_or_else(|| {
+        format!(
+            "Missing/Invalid field 'playerData.version': {}",
+            data["playerData"]["version"]
+        )
+    })?;
+    println!("hk_ver = {hk_ver}");
+
+    load_mod_data(data, map_data)
+}
+
+/// Reads a save with [`read_save_file`] and builds the randomizer state out of it. Unlike
+/// [`load_save`] this doesn't need `playerData`, exports of just the mod data don't have it.
+pub fn load_save_file(path: impl AsRef<Path>, map_data: &MapData) -> Result<SaveData, String> {
+    let path = path.as_ref();
+    let data = read_save_file(path)?;
+    if let Some(hk_ver) = data["playerData"]["version"].as_str() {
+        println!("hk_ver = {hk_ver}");
+    }
+
+    load_mod_data(&data, map_data).map_err(|e| format!("{}: {e}", path.display()))
+}
+
+/// The part of [`load_save`] that reads `PolymorphicModData`.
+fn load_mod_data(data: &JsonValue, map_data: &MapData) -> Result<SaveData, String> {
+    let rando_data = mod_data(
+        &data["PolymorphicModData"]["RandomizerMod"],
+        "data.PolymorphicModData.RandomizerMod",
+    )?;
+    let rando_ctx = mod_data(
+        &data["PolymorphicModData"]["context"],
+        "data.PolymorphicModData.context",
+    )?;
+
+    let mut transition_map = HashMap::new();
+    for obj in rando_ctx["transitionPlacements"].members() {
+        let src = obj["Source"]["Name"]
+            .as_str()
+            .ok_or_else(|| format!("Invalid transition placement: {obj}"))?;
+        let dst = obj["Target"]["Name"]
+            .as_str()
+            .ok_or_else(|| format!("Invalid transition placement: {obj}"))?;
+        transition_map.insert(src.into(), dst.into());
+    }
+
+    let mut visited_transitions = HashSet::new();
+    for (src, dst) in rando_data["TrackerData"]["visitedTransitions"].entries() {
+        let dst = dst
+            .as_str()
+            .ok_or_else(|| format!("Invalid visited transition: {src} -> {dst}"))?;
+        visited_transitions.insert(src.into());
+        visited_transitions.insert(dst.into());
+    }
+
+    let item_placements = item_placements(&rando_ctx)?;
+
+    let mut checked_locations = HashSet::new();
+    for location in rando_data["TrackerData"]["clearedLocations"].members() {
+        let location = location
+            .as_str()
+            .ok_or_else(|| format!("Invalid cleared location: {location}"))?;
+        checked_locations.insert(location.into());
+    }
+
+    let current_room = rando_ctx["StartDef"]["SceneName"]
+        .as_str()
+        .ok_or_else(|| {
+            format!(
+                "Missing/Invalid field 'rando_ctx.StartDef.SceneName': {}",
+                rando_ctx["StartDef"]["SceneName"]
+            )
+        })?
+        .into();
+
+    let seed = rando_data["GenerationSettings"]["Seed"].as_i64();
+
+    let mut rando_data = RandoData {
+        randomized_transitions: transition_map.keys().cloned().collect(),
+        transition_map,
+        visited_transitions,
+        room_positions: HashMap::new(),
+        world_positions: HashMap::new(),
+        vanilla_positions: HashMap::new(),
+        pinned_rooms: HashSet::new(),
+        world_pinned_rooms: HashSet::new(),
+        checked_locations,
+        item_placements,
+    };
+    // everything that isn't randomized goes to its vanilla destination
+    rando_data.fill_vanilla_transitions(map_data);
+
+    Ok(SaveData { current_room, seed, rando_data })
+}
+
+/// Reads a save from disk into the same shape as the `data` of a `loadSave` message.
+///
+/// Accepts a recorded/exported `loadSave` message, the save data itself (`playerData` +
+/// `PolymorphicModData`), or just the exported `PolymorphicModData` object.
+pub fn read_save_file(path: impl AsRef<Path>) -> Result<JsonValue, String> {
+    let path = path.as_ref();
+    let bytes = std::fs::read(path).map_err(|e| format!("{}: {e}", path.display()))?;
+    let source = String::from_utf8(bytes).map_err(|_| {
+        format!(
+            "{}: not a JSON save (encrypted .dat saves aren't supported, use the modded .json save or an export)",
+            path.display()
+        )
+    })?;
+
+    let mut json = json::parse(source.trim_start_matches('\u{feff}'))
+        .map_err(|e| format!("{}: {e}", path.display()))?;
+
+    if json["type"] == "loadSave" {
+        json = json["data"].take();
+    }
+
+    if json.has_key("PolymorphicModData") {
+        Ok(json)
+    } else if json.has_key("RandomizerMod") {
+        Ok(json::object! { "PolymorphicModData": json })
+    } else {
+        Err(format!(
+            "{}: no randomizer data found (expected 'PolymorphicModData' or 'RandomizerMod')",
+            path.display()
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Placements as Randomizer 4 saves them in `PolymorphicModData.context`, cut down to
+    /// what's around the names.
+    const ITEM_PLACEMENTS: &str = r#"{
+        "itemPlacements": [
+            {
+                "Item": {
+                    "$type": "RandomizerMod.RC.RandoModItem, RandomizerMod",
+                    "ItemDef": { "Name": "Mothwing_Cloak", "Pool": "Skill", "PriceCap": 500 },
+                    "item": {
+                        "$type": "RandomizerCore.LogicItems.BoolItem, RandomizerCore",
+                        "Name": "Mothwing_Cloak",
+                        "Term": "LEFTDASH"
+                    },
+                    "Name": "Mothwing_Cloak"
+                },
+                "Location": {
+                    "$type": "RandomizerMod.RC.RandoModLocation, RandomizerMod",
+                    "LocationDef": { "Name": "Sly", "SceneName": "Room_shop", "FlexibleCount": true },
+                    "costs": null,
+                    "logic": { "$type": "RandomizerCore.Logic.OptimizedLogicDef, RandomizerCore", "Name": "Sly" },
+                    "Name": "Sly"
+                },
+                "Index": 0
+            },
+            {
+                "Item": {
+                    "$type": "RandomizerMod.RC.RandoModItem, RandomizerMod",
+                    "ItemDef": { "Name": "Rancid_Egg", "Pool": "Egg", "PriceCap": 1 },
+                    "item": {
+                        "$type": "RandomizerCore.LogicItems.SingleItem, RandomizerCore",
+                        "Name": "Rancid_Egg",
+                        "Effect": { "Term": "RANCIDEGGS", "Value": 1 }
+                    },
+                    "Name": "Rancid_Egg"
+                },
+                "Location": {
+                    "$type": "RandomizerMod.RC.RandoModLocation, RandomizerMod",
+                    "LocationDef": { "Name": "Sly", "SceneName": "Room_shop", "FlexibleCount": true },
+                    "costs": null,
+                    "logic": { "$type": "RandomizerCore.Logic.OptimizedLogicDef, RandomizerCore", "Name": "Sly" },
+                    "Name": "Sly"
+                },
+                "Index": 1
+            },
+            {
+                "Item": {
+                    "$type": "RandomizerMod.RC.RandoModItem, RandomizerMod",
+                    "ItemDef": { "Name": "Geo_Chest-False_Knight", "Pool": "Geo", "PriceCap": 1 },
+                    "item": {
+                        "$type": "RandomizerCore.LogicItems.EmptyItem, RandomizerCore",
+                        "Name": "Geo_Chest-False_Knight"
+                    },
+                    "Name": "Geo_Chest-False_Knight"
+                },
+                "Location": {
+                    "$type": "RandomizerMod.RC.RandoModLocation, RandomizerMod",
+                    "LocationDef": { "Name": "Mothwing_Cloak", "SceneName": "Fungus1_04", "FlexibleCount": false },
+                    "costs": null,
+                    "logic": { "$type": "RandomizerCore.Logic.OptimizedLogicDef, RandomizerCore", "Name": "Mothwing_Cloak" },
+                    "Name": "Mothwing_Cloak"
+                },
+                "Index": 2
+            }
+        ]
+    }"#;
+
+    #[test]
+    fn items_by_location() {
+        let placements = item_placements(&json::parse(ITEM_PLACEMENTS).unwrap()).unwrap();
+
+        assert_eq!(placements.len(), 2);
+        assert_eq!(placements["Sly"], ["Mothwing_Cloak", "Rancid_Egg"]);
+        assert_eq!(placements["Mothwing_Cloak"], ["Geo_Chest-False_Knight"]);
+    }
+
+    #[test]
+    fn placement_without_names() {
+        let rando_ctx = json::parse(r#"{ "itemPlacements": [{ "Item": {}, "Location": {} }] }"#);
+        assert!(item_placements(&rando_ctx.unwrap()).is_err());
+
+        // older contexts without any placements are fine
+        assert!(item_placements(&json::object! {}).unwrap().is_empty());
+    }
+}
@@ -0,0 +1,179 @@
+use std::cmp::Reverse;
+
+use crate::data::{room::Room, MapData};
+
+/// A room matching a search, see [`search_rooms`].
+#[derive(Clone, Debug)]
+pub struct SearchResult {
+    pub room: String,
+    /// What the words of the query matched best besides the room key, e.g. the room's name or
+    /// one of its locations.
+    pub matched: Vec<String>,
+    pub score: i32,
+}
+
+/// How well `query` matches `text`, ignoring case. `None` if the characters of `query` don't
+/// all appear in `text` in order.
+///
+/// Characters that follow each other or start a word in `text` score higher, skipped
+/// characters a bit lower.
+#[must_use]
+pub fn fuzzy_score(query: &str, text: &str) -> Option<i32> {
+    let text: Vec<char> = text.to_lowercase().chars().collect();
+    let mut score = 0;
+    let mut next = 0;
+    for c in query.to_lowercase().chars() {
+        let i = next + text[next..].iter().position(|&t| t == c)?;
+
+        score += 10;
+        if i > 0 && i == next {
+            score += 15;
+        }
+        if i == 0 || !text[i - 1].is_alphanumeric() {
+            score += 20;
+        }
+        score -= (i - next).min(10) as i32;
+
+        next = i + 1;
+    }
+
+    Some(score)
+}
+
+/// The rooms whose key, name, area or item locations match every word of `query`, best
+/// matches first, at most `limit` of them.
+#[must_use]
+pub fn search_rooms(map_data: &MapData, query: &str, limit: usize) -> Vec<SearchResult> {
+    let words: Vec<&str> = query.split_whitespace().collect();
+    if words.is_empty() {
+        return Vec::new();
+    }
+
+    let mut results: Vec<SearchResult> = map_data
+        .rooms
+        .iter()
+        .filter_map(|(key, room)| {
+            let fields = search_fields(map_data, key, room);
+
+            // every word has to match some field
+            let mut score = 0;
+            let mut matched: Vec<usize> = Vec::new();
+            for word in &words {
+                let (best, field) = fields
+                    .iter()
+                    .enumerate()
+                    .filter_map(|(i, field)| fuzzy_score(word, field).map(|s| (s, i)))
+                    .max_by_key(|&(s, i)| (s, Reverse(i)))?;
+                score += best;
+                if field != 0 && !matched.contains(&field) {
+                    matched.push(field);
+                }
+            }
+
+            Some(SearchResult {
+                room: key.clone(),
+                matched: matched.into_iter().map(|i| fields[i].clone()).collect(),
+                score,
+            })
+        })
+        .collect();
+
+    results.sort_by(|a, b| b.score.cmp(&a.score).then_with(|| a.room.cmp(&b.room)));
+    results.truncate(limit);
+    results
+}
+
+/// The texts a room can be found by, starting with its key. Locations have `_` as spaces.
+fn search_fields(map_data: &MapData, key: &str, room: &Room) -> Vec<String> {
+    let mut fields = vec![key.to_owned()];
+    fields.extend(room.name.clone());
+    fields.extend(room.stag.clone());
+    if let Some(area) = &room.area {
+        fields.push(map_data.areas.get(area).unwrap_or(area).clone());
+    }
+    let mut items: Vec<&String> = room.items.keys().collect();
+    items.sort_unstable();
+    fields.extend(items.into_iter().map(|name| name.replace('_', " ")));
+    fields
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::load_mapdata;
+
+    /// Kingdom's Edge has no stag station in the game, `Deepnest_East_10` is made up.
+    fn map_data() -> MapData {
+        load_mapdata(
+            r#"{
+                "areas": { "Deepnest_East": "Kingdom's Edge", "Fungus2": "Fungal Wastes" },
+                "rooms": {
+                    "Deepnest_East_03": { "area": "Deepnest_East", "name": "Cornifer" },
+                    "Deepnest_East_10": {
+                        "area": "Deepnest_East",
+                        "name": "Edge Stag",
+                        "stag": "KingdomsEdge",
+                        "items": {
+                            "Kingdom's_Edge_Stag": {
+                                "randAction": "Stag", "randPool": "Stag", "randType": null
+                            }
+                        }
+                    },
+                    "Fungus2_02": {
+                        "area": "Fungus2",
+                        "name": "Queen's Station Stag",
+                        "stag": "FungalWastes"
+                    }
+                }
+            }"#,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn consecutive_characters_score_higher() {
+        assert!(fuzzy_score("stag", "Stag") > fuzzy_score("stag", "Staying"));
+    }
+
+    #[test]
+    fn word_starts_score_higher() {
+        // both skip the same characters, only one has "edge" start a word
+        assert!(fuzzy_score("edge", "Kingdom's Edge") > fuzzy_score("edge", "Kingdomsxedge"));
+        assert!(fuzzy_score("qs", "Queen's Station") > fuzzy_score("qs", "Queenxsstation"));
+    }
+
+    #[test]
+    fn no_match() {
+        assert_eq!(fuzzy_score("gats", "Stag"), None);
+        assert_eq!(fuzzy_score("stags", "Stag"), None);
+        assert_eq!(fuzzy_score("STAG", "stag"), fuzzy_score("stag", "stag"));
+
+        let map_data = map_data();
+        assert!(search_rooms(&map_data, "Greenpath", 10).is_empty());
+        assert!(search_rooms(&map_data, "  ", 10).is_empty());
+        // every word has to match
+        assert!(search_rooms(&map_data, "Fungal Wastes Cornifer", 10).is_empty());
+    }
+
+    #[test]
+    fn kingdoms_edge_stag() {
+        let map_data = map_data();
+        let results = search_rooms(&map_data, "Kingdom's Edge stag", 10);
+
+        let rooms: Vec<&str> = results.iter().map(|result| result.room.as_str()).collect();
+        assert_eq!(rooms, ["Deepnest_East_10"]);
+        assert!(results[0].matched.contains(&"Kingdom's Edge".to_owned()));
+
+        // the area alone finds all its rooms, the stag first since its name starts with "Edge"
+        let rooms: Vec<String> = search_rooms(&map_data, "edge", 10)
+            .into_iter()
+            .map(|result| result.room)
+            .collect();
+        assert_eq!(rooms, ["Deepnest_East_10", "Deepnest_East_03"]);
+
+        assert_eq!(
+            search_rooms(&map_data, "stag", 1)[0].room,
+            "Deepnest_East_10"
+        );
+    }
+}
@@ -1,4 +1,14 @@
-use crate::data::{MapData, RandoData};
+use std::collections::{BTreeSet, HashSet};
+
+use crate::{
+    data::{MapData, RandoData},
+    graph::{Graph, PathCosts},
+    layout::{self, Positions, VanillaLayout},
+    util::{grid::SpatialGrid, rect::Rect},
+};
+
+/// How far outside of a room still counts as pointing at it.
+const PICK_MARGIN: f32 = 5.0;
 
 #[allow(clippy::large_enum_variant)]
 pub enum GameState {
@@ -8,6 +18,7 @@ pub enum GameState {
 
 pub struct LoadedState {
     pub current_room: String,
+    pub seed: Option<i64>,
     pub player_x: f32,
     pub player_y: f32,
     pub rando_data: RandoData,
@@ -15,23 +26,214 @@ pub struct LoadedState {
     pub hovered_room: Option<String>,
     pub selected_room: Option<String>,
     pub dragging_room: bool,
+    pub view: View,
+    /// Which rooms are visible and where, see [`LoadedState::update_room_grid`].
+    pub room_grid: RoomGrid,
+    /// The visited transitions, see [`LoadedState::rebuild_graph`].
+    pub graph: Graph,
+}
+
+/// The rooms the current view shows and a [`SpatialGrid`] of where they are, only worked out
+/// again when they change.
+#[derive(Default)]
+pub struct RoomGrid {
+    grid: SpatialGrid<String>,
+    /// The view, depth and current room `rooms` are for, `None` if they need working out again.
+    rooms_for: Option<(View, u8, String)>,
+    rooms: BTreeSet<String>,
+    /// Where `rooms` were when `grid` was built.
+    positions: Vec<Option<(f32, f32)>>,
+}
+
+/// Which rooms are shown and how they're laid out.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum View {
+    /// The rooms around the current room, which stays in the middle.
+    Local,
+    /// Every reachable room in one layout that doesn't move with the player.
+    World,
+    /// Every reachable room, grouped by area and connected the vanilla way.
+    Vanilla,
+}
+
+impl View {
+    /// The room positions `rando_data` has for this view.
+    #[must_use]
+    pub fn positions(self, rando_data: &RandoData) -> &Positions {
+        match self {
+            View::Local => &rando_data.room_positions,
+            View::World => &rando_data.world_positions,
+            View::Vanilla => &rando_data.vanilla_positions,
+        }
+    }
+
+    pub fn positions_mut(self, rando_data: &mut RandoData) -> &mut Positions {
+        match self {
+            View::Local => &mut rando_data.room_positions,
+            View::World => &mut rando_data.world_positions,
+            View::Vanilla => &mut rando_data.vanilla_positions,
+        }
+    }
+
+    /// The rooms pinned in this view, `None` if its rooms can't be pinned.
+    #[must_use]
+    pub fn pinned_rooms(self, rando_data: &RandoData) -> Option<&HashSet<String>> {
+        match self {
+            View::Local => Some(&rando_data.pinned_rooms),
+            View::World => Some(&rando_data.world_pinned_rooms),
+            View::Vanilla => None,
+        }
+    }
+
+    pub fn pinned_rooms_mut(self, rando_data: &mut RandoData) -> Option<&mut HashSet<String>> {
+        match self {
+            View::Local => Some(&mut rando_data.pinned_rooms),
+            View::World => Some(&mut rando_data.world_pinned_rooms),
+            View::Vanilla => None,
+        }
+    }
 }
 
 impl LoadedState {
     pub fn update(&mut self, map_data: &MapData) {
         self.camera.update(
             map_data,
-            &self.rando_data,
+            self.view.positions(&self.rando_data),
             self.player_x,
             self.player_y,
             &self.current_room,
         );
     }
+
+    /// Builds [`LoadedState::graph`] again, has to be called after the visited transitions or
+    /// the map data changed.
+    pub fn rebuild_graph(&mut self, map_data: &MapData) {
+        self.graph = Graph::new(map_data, &self.rando_data, self.graph.costs());
+        self.room_grid.rooms_for = None;
+    }
+
+    /// Builds [`LoadedState::graph`] again if `costs` changed.
+    pub fn set_path_costs(&mut self, map_data: &MapData, costs: PathCosts) {
+        if costs != self.graph.costs() {
+            self.graph = Graph::new(map_data, &self.rando_data, costs);
+        }
+    }
+
+    /// Brings [`LoadedState::room_grid`] up to date with the rooms the current view shows and
+    /// their positions. Only does the work again for what changed since the last call.
+    pub fn update_room_grid(&mut self, map_data: &MapData, depth: u8) {
+        let up_to_date = matches!(
+            &self.room_grid.rooms_for,
+            Some((view, rooms_depth, room))
+                if *view == self.view && *rooms_depth == depth && *room == self.current_room
+        );
+        if !up_to_date {
+            self.room_grid.rooms = match self.view {
+                View::Local => self.graph.rooms_within(&self.current_room, depth),
+                View::World | View::Vanilla => self.graph.reachable_rooms(&self.current_room),
+            };
+            self.room_grid.rooms_for = Some((self.view, depth, self.current_room.clone()));
+            self.room_grid.positions.clear();
+        }
+
+        let positions = self.view.positions(&self.rando_data);
+        let room_grid = &mut self.room_grid;
+        let current: Vec<_> = room_grid
+            .rooms
+            .iter()
+            .map(|key| positions.get(key).copied())
+            .collect();
+        if current == room_grid.positions && up_to_date {
+            return;
+        }
+
+        // in the order they're drawn, so the last one found at a spot is on top
+        let mut grid = SpatialGrid::default();
+        for (key, pos) in room_grid.rooms.iter().zip(&current) {
+            if let (Some(room), Some(pos)) = (map_data.rooms.get(key), pos) {
+                grid.insert(key.clone(), room.bounds_at(*pos));
+            }
+        }
+        room_grid.grid = grid;
+        room_grid.positions = current;
+    }
+
+    /// The visible room drawn on top at `point` (in map units), as of the last
+    /// [`LoadedState::update_room_grid`].
+    #[must_use]
+    pub fn room_at(&self, point: [f32; 2]) -> Option<&String> {
+        let mut area = Rect::new(point[0], point[1], 0.0, 0.0);
+        area.inflate(PICK_MARGIN);
+        self.room_grid.grid.query(&area).last().map(|(key, _)| key)
+    }
+
+    /// Like [`LoadedState::room_at`], also giving `point` in the room's own coordinates (see
+    /// [`crate::data::room::Room::local_point`]).
+    #[must_use]
+    pub fn room_point_at(
+        &self,
+        map_data: &MapData,
+        point: [f32; 2],
+    ) -> Option<(&String, (f32, f32))> {
+        let key = self.room_at(point)?;
+        let room = map_data.rooms.get(key)?;
+        let pos = *self.positions().get(key)?;
+        Some((key, room.local_point(pos, point)))
+    }
+
+    /// The room positions of the current view.
+    #[must_use]
+    pub fn positions(&self) -> &Positions {
+        self.view.positions(&self.rando_data)
+    }
+
+    pub fn positions_mut(&mut self) -> &mut Positions {
+        self.view.positions_mut(&mut self.rando_data)
+    }
+
+    /// The rooms pinned in the current view, see [`View::pinned_rooms`].
+    #[must_use]
+    pub fn pinned_rooms(&self) -> Option<&HashSet<String>> {
+        self.view.pinned_rooms(&self.rando_data)
+    }
+
+    pub fn pinned_rooms_mut(&mut self) -> Option<&mut HashSet<String>> {
+        self.view.pinned_rooms_mut(&mut self.rando_data)
+    }
+
+    /// The rooms the current view shows in the order they're drawn, as of the last
+    /// [`LoadedState::update_room_grid`].
+    #[must_use]
+    pub fn visible_rooms(&self) -> &BTreeSet<String> {
+        &self.room_grid.rooms
+    }
+
+    /// Switches to another view, keeping the camera on the same spot relative to the current
+    /// room.
+    pub fn set_view(&mut self, view: View, vanilla_layout: &mut VanillaLayout) {
+        if view == View::Vanilla {
+            layout::fill_vanilla_positions(&mut self.rando_data, vanilla_layout);
+        }
+
+        let before = self.positions().get(&self.current_room).copied();
+        self.view = view;
+        let after = self.positions().get(&self.current_room).copied();
+
+        if let (Some(before), Some(after)) = (before, after) {
+            self.camera.shift(after.0 - before.0, after.1 - before.1);
+        }
+    }
 }
 
+pub const MIN_ZOOM: f32 = 0.1;
+pub const MAX_ZOOM: f32 = 8.0;
+
+/// `x`/`y` is the point of the map in the middle of the window, `zoom` is how many pixels
+/// one map unit takes up.
 pub struct Camera {
     pub x: f32,
     pub y: f32,
+    pub zoom: f32,
     pub target: CameraTarget,
 }
 
@@ -43,10 +245,51 @@ pub enum CameraTarget {
 }
 
 impl Camera {
+    #[must_use]
+    pub fn new(target: CameraTarget) -> Self {
+        Self { x: 0.0, y: 0.0, zoom: 1.0, target }
+    }
+
+    /// Converts an offset from the middle of the window into a map position.
+    #[must_use]
+    pub fn screen_to_world(&self, (x, y): (f32, f32)) -> (f32, f32) {
+        (self.x + x / self.zoom, self.y + y / self.zoom)
+    }
+
+    /// Zooms by `factor` while keeping the map position under `cursor` (an offset from the
+    /// middle of the window) where it is. While following the player or a room it zooms in on
+    /// that instead, so it keeps following it.
+    pub fn zoom_at(&mut self, factor: f32, cursor: (f32, f32)) {
+        let (world_x, world_y) = self.screen_to_world(cursor);
+        self.zoom = (self.zoom * factor).clamp(MIN_ZOOM, MAX_ZOOM);
+        if let CameraTarget::Point { .. } = self.target {
+            self.x = world_x - cursor.0 / self.zoom;
+            self.y = world_y - cursor.1 / self.zoom;
+            self.target = CameraTarget::Point { x: self.x, y: self.y };
+        }
+    }
+
+    /// Moves the view by a mouse drag of `dx`/`dy` pixels, which stops following the target.
+    pub fn pan(&mut self, dx: f32, dy: f32) {
+        self.x -= dx / self.zoom;
+        self.y -= dy / self.zoom;
+        self.target = CameraTarget::Point { x: self.x, y: self.y };
+    }
+
+    /// Moves the camera along with the map when the positions it's looking at are moved.
+    pub fn shift(&mut self, dx: f32, dy: f32) {
+        self.x += dx;
+        self.y += dy;
+        if let CameraTarget::Point { x, y } = &mut self.target {
+            *x += dx;
+            *y += dy;
+        }
+    }
+
     pub fn update(
         &mut self,
         map_data: &MapData,
-        rando_data: &RandoData,
+        positions: &Positions,
         player_x: f32,
         player_y: f32,
         current_room: &String,
@@ -54,7 +297,7 @@ impl Camera {
         let (tx, ty) = match &self.target {
             CameraTarget::Point { x, y } => (*x, *y),
             CameraTarget::Room(r) => {
-                if let Some(pos) = rando_data.room_positions.get(r) {
+                if let Some(pos) = positions.get(r) {
                     *pos
                 } else {
                     self.target = CameraTarget::PlayerRoom;
@@ -62,9 +305,9 @@ impl Camera {
                 }
             },
             CameraTarget::Player => {
-                if let Some(pos) = rando_data.room_positions.get(current_room) {
+                if let Some(pos) = positions.get(current_room) {
                     if let Some(room) = map_data.rooms.get(current_room) {
-                        let bounds = room.calc_bounds();
+                        let bounds = room.bounds();
                         (pos.0 + player_x, pos.1 - player_y + bounds.h)
                     } else {
                         (pos.0 + player_x, pos.1 - player_y)
@@ -74,7 +317,7 @@ impl Camera {
                 }
             },
             CameraTarget::PlayerRoom => {
-                if let Some(pos) = rando_data.room_positions.get(current_room) {
+                if let Some(pos) = positions.get(current_room) {
                     *pos
                 } else {
                     (0.0, 0.0)
@@ -86,3 +329,96 @@ impl Camera {
         self.y += (ty - self.y) * 0.125;
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    #[allow(clippy::float_cmp)] // powers of two, so exact
+    fn zoom_at_cursor() {
+        let mut camera = Camera::new(CameraTarget::Point { x: 0.0, y: 0.0 });
+        let under_cursor = camera.screen_to_world((100.0, -50.0));
+        camera.zoom_at(2.0, (100.0, -50.0));
+
+        assert_eq!(camera.zoom, 2.0);
+        assert_eq!(camera.screen_to_world((100.0, -50.0)), under_cursor);
+        assert!(matches!(camera.target, CameraTarget::Point { x, y } if (x, y) == (50.0, -25.0)));
+    }
+
+    #[test]
+    #[allow(clippy::float_cmp)] // nothing is calculated
+    fn zoom_keeps_following() {
+        let mut camera = Camera::new(CameraTarget::Player);
+        camera.x = 10.0;
+        camera.zoom_at(2.0, (100.0, -50.0));
+        assert!(matches!(camera.target, CameraTarget::Player));
+        assert_eq!((camera.x, camera.y, camera.zoom), (10.0, 0.0, 2.0));
+
+        camera.target = CameraTarget::Room("Town".into());
+        camera.zoom_at(100.0, (100.0, -50.0));
+        assert!(matches!(camera.target, CameraTarget::Room(_)));
+        assert_eq!(camera.zoom, MAX_ZOOM);
+    }
+
+    fn loaded_state(map_data: &MapData) -> LoadedState {
+        let rando_data = RandoData::vanilla(map_data);
+        LoadedState {
+            current_room: "A".into(),
+            seed: None,
+            player_x: 0.0,
+            player_y: 0.0,
+            graph: Graph::new(map_data, &rando_data, PathCosts::default()),
+            rando_data,
+            camera: Camera::new(CameraTarget::Player),
+            hovered_room: None,
+            selected_room: None,
+            dragging_room: false,
+            view: View::World,
+            room_grid: RoomGrid::default(),
+        }
+    }
+
+    #[test]
+    fn room_at_picks_the_room_on_top() {
+        let map_data = crate::data::load_mapdata(
+            r#"{
+                "areas": {},
+                "rooms": {
+                    "A": { "transitions": { "right1": { "to": "B[left1]", "x": 40, "y": 5 } } },
+                    "B": { "transitions": { "left1": { "to": "A[right1]", "x": 0, "y": 5 } } }
+                }
+            }"#,
+        )
+        .unwrap();
+        let mut state = loaded_state(&map_data);
+        // partly on top of each other, B is drawn last
+        state.rando_data.world_positions =
+            [("A".into(), (0.0, 0.0)), ("B".into(), (35.0, 0.0))].into();
+        state.update_room_grid(&map_data, 2);
+
+        let a = map_data.rooms["A"].bounds_at((0.0, 0.0));
+        let b = map_data.rooms["B"].bounds_at((35.0, 0.0));
+        let overlap = a.intersection(&b).unwrap().center();
+        let y = overlap.y;
+        // further out than `PICK_MARGIN`, so only one room is close enough
+        let only_a = a.right() + 4.0;
+        let only_b = b.left() - 4.0;
+        assert!(only_b < a.left() - PICK_MARGIN && only_a > b.right() + PICK_MARGIN);
+
+        assert_eq!(state.visible_rooms().iter().collect::<Vec<_>>(), ["A", "B"]);
+        assert_eq!(state.room_at([overlap.x, y]).unwrap(), "B");
+        assert_eq!(state.room_at([only_a, y]).unwrap(), "A");
+        assert_eq!(state.room_at([only_b, y]).unwrap(), "B");
+
+        // moving a room updates the grid
+        state
+            .rando_data
+            .world_positions
+            .insert("B".into(), (500.0, 0.0));
+        state.update_room_grid(&map_data, 2);
+        assert_eq!(state.room_at([overlap.x, y]).unwrap(), "A");
+        assert_eq!(state.room_at([only_b, y]), None);
+        assert_eq!(state.room_at([b.center().x + 465.0, y]).unwrap(), "B");
+    }
+}
@@ -0,0 +1,80 @@
+/// Command line flags, given as either `--flag value` or `--flag=value`.
+pub struct Flags<I> {
+    args: I,
+    inline_value: Option<String>,
+}
+
+impl<I: Iterator<Item = String>> Flags<I> {
+    pub fn new(args: impl IntoIterator<IntoIter = I>) -> Self {
+        Self { args: args.into_iter(), inline_value: None }
+    }
+
+    /// The next flag. A value after `=` is kept for [`Flags::value`].
+    pub fn next_flag(&mut self) -> Option<String> {
+        let arg = self.args.next()?;
+        match arg.split_once('=') {
+            Some((flag, value)) if flag.starts_with("--") => {
+                self.inline_value = Some(value.to_owned());
+                Some(flag.to_owned())
+            },
+            _ => {
+                self.inline_value = None;
+                Some(arg)
+            },
+        }
+    }
+
+    /// The value of `flag`, after its `=` or the next argument.
+    pub fn value(&mut self, flag: &str) -> Result<String, String> {
+        self.inline_value
+            .take()
+            .or_else(|| self.args.next())
+            .ok_or_else(|| format!("Missing value for {flag}"))
+    }
+
+    /// [`Flags::value`] parsed as a `T`.
+    pub fn parse<T: std::str::FromStr>(&mut self, flag: &str) -> Result<T, String> {
+        self.value(flag)?
+            .parse()
+            .map_err(|_| format!("Invalid value for {flag}"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn flags(args: &[&str]) -> Flags<impl Iterator<Item = String>> {
+        Flags::new(args.iter().map(|arg| (*arg).to_owned()).collect::<Vec<_>>())
+    }
+
+    #[test]
+    fn separate_and_inline_values() {
+        let mut flags = flags(&["--depth", "3", "--seed=4", "-h", "--room=A=B"]);
+
+        assert_eq!(flags.next_flag().as_deref(), Some("--depth"));
+        assert_eq!(flags.parse::<u8>("--depth"), Ok(3));
+        assert_eq!(flags.next_flag().as_deref(), Some("--seed"));
+        assert_eq!(flags.value("--seed").as_deref(), Ok("4"));
+        assert_eq!(flags.next_flag().as_deref(), Some("-h"));
+        assert_eq!(flags.next_flag().as_deref(), Some("--room"));
+        assert_eq!(flags.value("--room").as_deref(), Ok("A=B"));
+        assert_eq!(flags.next_flag(), None);
+    }
+
+    #[test]
+    fn missing_and_invalid_values() {
+        let mut flags = flags(&["--depth=x", "--seed"]);
+
+        flags.next_flag();
+        assert_eq!(
+            flags.parse::<u8>("--depth"),
+            Err("Invalid value for --depth".into())
+        );
+        flags.next_flag();
+        assert_eq!(
+            flags.value("--seed"),
+            Err("Missing value for --seed".into())
+        );
+    }
+}
@@ -0,0 +1,141 @@
+use std::collections::HashMap;
+
+use super::rect::Rect;
+
+/// Buckets rects into square cells so looking up the rects around a spot doesn't have to go
+/// through all of them.
+pub struct SpatialGrid<T> {
+    cell_size: f32,
+    entries: Vec<(T, Rect)>,
+    cells: HashMap<(i32, i32), Vec<usize>>,
+}
+
+impl<T> SpatialGrid<T> {
+    #[must_use]
+    pub fn new(cell_size: f32) -> Self {
+        Self {
+            cell_size,
+            entries: Vec::new(),
+            cells: HashMap::new(),
+        }
+    }
+
+    pub fn insert(&mut self, value: T, rect: Rect) {
+        let index = self.entries.len();
+        for cell in self.cells_of(&rect) {
+            self.cells.entry(cell).or_default().push(index);
+        }
+        self.entries.push((value, rect));
+    }
+
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// The rects overlapping `rect`, in the order they were inserted.
+    pub fn query(&self, rect: &Rect) -> impl Iterator<Item = (&T, &Rect)> {
+        let mut indices: Vec<usize> = self
+            .cells_of(rect)
+            .filter_map(|cell| self.cells.get(&cell))
+            .flatten()
+            .copied()
+            .collect();
+        indices.sort_unstable();
+        indices.dedup();
+
+        let rect = *rect;
+        indices.into_iter().filter_map(move |i| {
+            let (value, other) = &self.entries[i];
+            other.overlaps(&rect).then_some((value, other))
+        })
+    }
+
+    fn cells_of(&self, rect: &Rect) -> impl Iterator<Item = (i32, i32)> {
+        let cell = |v: f32| (v / self.cell_size).floor() as i32;
+        let (left, right) = (cell(rect.left()), cell(rect.right()));
+        let (top, bottom) = (cell(rect.top()), cell(rect.bottom()));
+        (left..=right).flat_map(move |x| (top..=bottom).map(move |y| (x, y)))
+    }
+}
+
+impl<T> Default for SpatialGrid<T> {
+    /// Cells about the size of a small room.
+    fn default() -> Self {
+        Self::new(256.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn query(grid: &SpatialGrid<&'static str>, rect: Rect) -> Vec<&'static str> {
+        grid.query(&rect).map(|(value, _)| *value).collect()
+    }
+
+    #[test]
+    fn rect_across_cells_is_found_once() {
+        let mut grid = SpatialGrid::new(10.0);
+        // covers 3x3 cells
+        grid.insert("big", Rect::new(5.0, 5.0, 20.0, 20.0));
+        grid.insert("small", Rect::new(1.0, 1.0, 2.0, 2.0));
+
+        assert_eq!(
+            query(&grid, Rect::new(0.0, 0.0, 30.0, 30.0)),
+            ["big", "small"]
+        );
+        assert_eq!(query(&grid, Rect::new(22.0, 22.0, 1.0, 1.0)), ["big"]);
+        assert_eq!(
+            query(&grid, Rect::new(26.0, 5.0, 1.0, 1.0)),
+            Vec::<&str>::new()
+        );
+    }
+
+    #[test]
+    fn cell_boundaries() {
+        let mut grid = SpatialGrid::new(10.0);
+        // ends exactly where the next cell starts
+        grid.insert("left", Rect::new(0.0, 0.0, 10.0, 10.0));
+        grid.insert("right", Rect::new(10.0, 0.0, 10.0, 10.0));
+
+        // touching counts as overlapping, like `Rect::overlaps`
+        assert_eq!(
+            query(&grid, Rect::new(10.0, 5.0, 0.0, 0.0)),
+            ["left", "right"]
+        );
+        assert_eq!(query(&grid, Rect::new(9.0, 5.0, 0.5, 0.0)), ["left"]);
+        assert_eq!(query(&grid, Rect::new(10.5, 5.0, 0.0, 0.0)), ["right"]);
+    }
+
+    #[test]
+    fn negative_coordinates() {
+        let mut grid = SpatialGrid::new(10.0);
+        grid.insert("a", Rect::new(-15.0, -15.0, 10.0, 10.0));
+        grid.insert("b", Rect::new(-5.0, -5.0, 10.0, 10.0));
+
+        // -0.5 is in cell -1 and 0.5 in cell 0, not both in cell 0
+        assert_eq!(query(&grid, Rect::new(-0.5, -0.5, 0.0, 0.0)), ["b"]);
+        assert_eq!(query(&grid, Rect::new(-14.0, -6.0, 0.0, 0.0)), ["a"]);
+        assert_eq!(query(&grid, Rect::new(-6.0, -6.0, 2.0, 2.0)), ["a", "b"]);
+        assert_eq!(
+            query(&grid, Rect::new(-30.0, -30.0, 5.0, 5.0)),
+            Vec::<&str>::new()
+        );
+    }
+
+    #[test]
+    fn insertion_order() {
+        let mut grid = SpatialGrid::new(10.0);
+        for value in ["c", "a", "b"] {
+            grid.insert(value, Rect::new(0.0, 0.0, 5.0, 5.0));
+        }
+        assert_eq!(query(&grid, Rect::new(1.0, 1.0, 0.0, 0.0)), ["c", "a", "b"]);
+        assert_eq!(grid.len(), 3);
+    }
+}
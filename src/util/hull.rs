@@ -0,0 +1,33 @@
+use super::rect::Point;
+
+/// Smallest convex polygon around `points`, without collinear points.
+#[must_use]
+pub fn convex_hull(mut points: Vec<Point>) -> Vec<Point> {
+    // Andrew's monotone chain
+    points.sort_by(|a, b| a.x.total_cmp(&b.x).then(a.y.total_cmp(&b.y)));
+    points.dedup();
+    if points.len() < 3 {
+        return points;
+    }
+
+    let mut hull = half_hull(points.iter().copied());
+    hull.extend(half_hull(points.iter().rev().copied()));
+    hull
+}
+
+/// One side of the hull of points sorted along x, without the last point since that starts
+/// the other side.
+fn half_hull(points: impl Iterator<Item = Point>) -> Vec<Point> {
+    let cross =
+        |o: Point, a: Point, b: Point| (a.x - o.x) * (b.y - o.y) - (a.y - o.y) * (b.x - o.x);
+
+    let mut half: Vec<Point> = Vec::new();
+    for point in points {
+        while half.len() >= 2 && cross(half[half.len() - 2], half[half.len() - 1], point) <= 0.0 {
+            half.pop();
+        }
+        half.push(point);
+    }
+    half.pop();
+    half
+}
@@ -1,4 +1,5 @@
-pub mod color_ext;
-pub mod rect_ext;
+pub mod flags;
+pub mod grid;
+pub mod hull;
+pub mod rect;
 pub mod split;
-pub mod transform_stack;
@@ -0,0 +1,98 @@
+/// Axis aligned rectangle, `y` grows downwards like screen coordinates.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Rect {
+    pub x: f32,
+    pub y: f32,
+    pub w: f32,
+    pub h: f32,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Point {
+    pub x: f32,
+    pub y: f32,
+}
+
+impl Rect {
+    #[must_use]
+    pub const fn new(x: f32, y: f32, w: f32, h: f32) -> Self {
+        Self { x, y, w, h }
+    }
+
+    #[must_use]
+    pub fn left(&self) -> f32 {
+        self.x
+    }
+
+    #[must_use]
+    pub fn right(&self) -> f32 {
+        self.x + self.w
+    }
+
+    #[must_use]
+    pub fn top(&self) -> f32 {
+        self.y
+    }
+
+    #[must_use]
+    pub fn bottom(&self) -> f32 {
+        self.y + self.h
+    }
+
+    #[must_use]
+    pub fn center(&self) -> Point {
+        Point { x: self.x + self.w / 2.0, y: self.y + self.h / 2.0 }
+    }
+
+    pub fn translate(&mut self, offset: [f32; 2]) {
+        self.x += offset[0];
+        self.y += offset[1];
+    }
+
+    pub fn inflate(&mut self, amount: f32) {
+        self.x -= amount;
+        self.y -= amount;
+        self.w += amount * 2.0;
+        self.h += amount * 2.0;
+    }
+
+    #[must_use]
+    pub fn contains(&self, point: [f32; 2]) -> bool {
+        point[0] >= self.left()
+            && point[0] <= self.right()
+            && point[1] >= self.top()
+            && point[1] <= self.bottom()
+    }
+
+    #[must_use]
+    pub fn overlaps(&self, other: &Rect) -> bool {
+        self.left() <= other.right()
+            && self.right() >= other.left()
+            && self.top() <= other.bottom()
+            && self.bottom() >= other.top()
+    }
+
+    /// The overlapping area of the two rects, if any.
+    #[must_use]
+    pub fn intersection(&self, other: &Rect) -> Option<Rect> {
+        if !self.overlaps(other) {
+            return None;
+        }
+
+        let left = self.left().max(other.left());
+        let top = self.top().max(other.top());
+        let right = self.right().min(other.right());
+        let bottom = self.bottom().min(other.bottom());
+        Some(Rect::new(left, top, right - left, bottom - top))
+    }
+
+    /// The smallest rect that covers both.
+    #[must_use]
+    pub fn union(&self, other: &Rect) -> Rect {
+        let left = self.left().min(other.left());
+        let top = self.top().min(other.top());
+        let right = self.right().max(other.right());
+        let bottom = self.bottom().max(other.bottom());
+        Rect::new(left, top, right - left, bottom - top)
+    }
+}
@@ -29,7 +29,7 @@ impl<'a, K: Eq + std::hash::Hash, V> Deref for SplitWrapper<'a, K, V> {
     }
 }
 
-impl<'a, K: Eq + std::hash::Hash, V> DerefMut for SplitWrapper<'a, K, V> {
+impl<K: Eq + std::hash::Hash, V> DerefMut for SplitWrapper<'_, K, V> {
     fn deref_mut(&mut self) -> &mut Self::Target {
         self.inner.as_mut().unwrap()
     }
@@ -37,12 +37,12 @@ impl<'a, K: Eq + std::hash::Hash, V> DerefMut for SplitWrapper<'a, K, V> {
 
 // TODO: docs
 pub trait GetSplit<'a, K: Eq + std::hash::Hash + Clone, V> {
-    fn split(&mut self, key: &K) -> Option<SplitWrapper<K, V>>;
+    fn split(&mut self, key: &K) -> Option<SplitWrapper<'_, K, V>>;
 }
 
 #[allow(clippy::implicit_hasher)]
-impl<'a, K: Clone + std::hash::Hash + Eq, V> GetSplit<'a, K, V> for HashMap<K, V> {
-    fn split(&mut self, key: &K) -> Option<SplitWrapper<K, V>> {
+impl<K: Clone + std::hash::Hash + Eq, V> GetSplit<'_, K, V> for HashMap<K, V> {
+    fn split(&mut self, key: &K) -> Option<SplitWrapper<'_, K, V>> {
         let m = self.remove(key)?;
         Some(SplitWrapper { key: Some(key.clone()), inner: Some((m, self)) })
     }